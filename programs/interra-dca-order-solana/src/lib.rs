@@ -1,29 +1,66 @@
 // Solana DCA Order Contract (Anchor version)
+// `#[program]` emits its IDL handlers, which call the deprecated
+// `AccountInfo::realloc`, next to the program module rather than inside it, so
+// an allow on the module doesn't reach them.
+#![allow(deprecated)]
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
 use anchor_lang::system_program;
-use anchor_spl::associated_token::get_associated_token_address;
-use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer as TokenTransfer};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token;
+use anchor_spl::token_interface::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_interface::{
+    self, get_mint_extension_data, CloseAccount, HarvestWithheldTokensToMint, Mint, SyncNative,
+    TokenAccount, TokenInterface, TransferChecked,
+};
 
+use std::cmp::Ordering;
 use std::str::FromStr;
 
 // Program ID
 declare_id!("GXjKcJ6RHuBuKMRe8SDtqosckaiVcGradnv9yYByR8TG");
 
+/// Hard cap on `platform_fee`, in bps (5%).
+pub const MAX_PLATFORM_FEE: u16 = 500;
+/// Delay between scheduling a `platform_fee` change and being able to apply it.
+pub const FEE_CHANGE_DELAY: i64 = 2 * 24 * 60 * 60;
+/// Interra chain id of Solana; orders always start here.
+pub const SOLANA_CHAIN_ID: u64 = 10002;
+/// How long a completed or cancelled order, or a confirmed execution receipt,
+/// stays readable before `close_order` / `close_receipt` may reclaim its rent.
+pub const ORDER_CLOSE_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// An active order whose pending slice is overdue by more than this is
+/// recorded as `Expired` rather than `Cancelled` when it is cancelled.
+pub const ORDER_EXPIRY_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Pyth pull-oracle receiver; owns every `PriceUpdateV2` account.
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+// sha256("account:PriceUpdateV2")[..8]
+const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
 #[program]
 pub mod interra_dca_order_solana {
-    use anchor_lang::context;
-
     use super::*;
 
     pub fn initialize(ctx: Context<Initialize>, platform_fee: u16, treasury: Pubkey) -> Result<()> {
-        require!(platform_fee < 10000, CustomError::InvalidPlatformFee);
+        require!(
+            platform_fee <= MAX_PLATFORM_FEE,
+            CustomError::InvalidPlatformFee
+        );
 
         let config = &mut ctx.accounts.global_config;
         config.owner = ctx.accounts.signer.key();
         config.platform_fee = platform_fee;
         config.treasury = treasury;
         config.paused = false;
-        config.reserved = [0; 128]; // Initialize reserved space to zero
+        config.frozen = false;
+        config.pending_platform_fee = 0;
+        config.fee_change_eta = 0;
+        config.max_referral_bps = 0;
+        config.pending_owner = Pubkey::default();
+        config.pauser = config.owner;
+        config.fee_manager = config.owner;
+        config.treasury_manager = config.owner;
         emit!(Initialized {
             owner: config.owner,
             platform_fee: config.platform_fee,
@@ -33,19 +70,73 @@ pub mod interra_dca_order_solana {
         Ok(())
     }
 
+    /// Grows a `GlobalConfig` written by an older build, whose account is too
+    /// short for the current layout and no longer deserializes. Fields added
+    /// since start zeroed: not frozen, no pending fee change, and referrals
+    /// off until `set_max_referral_bps`.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config = ctx.accounts.global_config.to_account_info();
+        let old_len = config.data_len();
+        let new_len = 8 + GlobalConfig::SIZE;
+        require!(old_len < new_len, CustomError::ConfigAlreadyMigrated);
+        {
+            let data = config.try_borrow_data()?;
+            require!(
+                data.len() >= 40 && data[..8] == *GlobalConfig::DISCRIMINATOR,
+                CustomError::InvalidParameter
+            );
+            // `owner` leads every layout so far.
+            require!(
+                data[8..40] == ctx.accounts.owner.key().to_bytes(),
+                CustomError::Unauthorized
+            );
+        }
+
+        let top_up = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(config.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: config.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        config.resize(new_len)?;
+        let mut data = config.try_borrow_mut_data()?;
+        data[old_len..].fill(0);
+        // `platform_fee` follows `owner` in every layout. Older layouts
+        // allowed fees above today's cap, which orders would snapshot.
+        let platform_fee = u16::from_le_bytes([data[40], data[41]]);
+        data[40..42].copy_from_slice(&platform_fee.min(MAX_PLATFORM_FEE).to_le_bytes());
+        drop(data);
+
+        emit!(ConfigMigrated {
+            old_len: old_len as u64,
+            new_len: new_len as u64,
+        });
+
+        Ok(())
+    }
+
+    /// A changed `new_platform_fee` is only scheduled, see `set_platform_fee`.
     pub fn update_config(
         ctx: Context<UpdateConfig>,
-        new_owner: Pubkey,
         new_platform_fee: u16,
         new_treasury: Pubkey,
         new_paused: bool,
     ) -> Result<()> {
-        require!(new_platform_fee < 10000, CustomError::InvalidPlatformFee);
-
         let config = &mut ctx.accounts.global_config;
+        require!(new_paused || !config.frozen, CustomError::ProgramFrozen);
 
-        config.owner = new_owner;
-        config.platform_fee = new_platform_fee;
+        if new_platform_fee != config.platform_fee {
+            schedule_fee_change(config, new_platform_fee)?;
+        }
         config.treasury = new_treasury;
         config.paused = new_paused;
 
@@ -59,17 +150,385 @@ pub mod interra_dca_order_solana {
         Ok(())
     }
 
+    /// First step of an ownership transfer; `Pubkey::default()` withdraws a
+    /// pending proposal.
+    pub fn propose_owner(ctx: Context<UpdateConfig>, new_owner: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        config.pending_owner = new_owner;
+
+        emit!(OwnerProposed {
+            owner: config.owner,
+            pending_owner: config.pending_owner,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        let previous_owner = config.owner;
+        config.owner = config.pending_owner;
+        config.pending_owner = Pubkey::default();
+
+        emit!(OwnerAccepted {
+            previous_owner,
+            owner: config.owner,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_roles(
+        ctx: Context<UpdateConfig>,
+        pauser: Pubkey,
+        fee_manager: Pubkey,
+        treasury_manager: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        config.pauser = pauser;
+        config.fee_manager = fee_manager;
+        config.treasury_manager = treasury_manager;
+
+        emit!(RolesUpdated {
+            pauser,
+            fee_manager,
+            treasury_manager,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<SetConfigField>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        require!(
+            config.is_pauser(&ctx.accounts.authority.key()),
+            CustomError::Unauthorized
+        );
+        require!(paused || !config.frozen, CustomError::ProgramFrozen);
+        let old = config.paused;
+        config.paused = paused;
+
+        emit!(PausedChanged { old, new: paused });
+
+        Ok(())
+    }
+
+    /// Emergency stop for the catastrophic case: unlike `paused`, this also
+    /// blocks cancellations and withdrawals. Freezing implies pausing.
+    pub fn set_frozen(ctx: Context<UpdateConfig>, frozen: bool) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        let old = config.frozen;
+        config.frozen = frozen;
+        if frozen && !config.paused {
+            config.paused = true;
+            emit!(PausedChanged {
+                old: false,
+                new: true,
+            });
+        }
+
+        emit!(FrozenChanged { old, new: frozen });
+
+        Ok(())
+    }
+
+    /// Schedules a new `platform_fee`, applied by `apply_platform_fee` once
+    /// `FEE_CHANGE_DELAY` has passed. Open orders keep their snapshotted fee.
+    pub fn set_platform_fee(ctx: Context<SetConfigField>, platform_fee: u16) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        require!(
+            config.is_fee_manager(&ctx.accounts.authority.key()),
+            CustomError::Unauthorized
+        );
+        schedule_fee_change(config, platform_fee)
+    }
+
+    pub fn apply_platform_fee(ctx: Context<SetConfigField>) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        require!(
+            config.is_fee_manager(&ctx.accounts.authority.key()),
+            CustomError::Unauthorized
+        );
+        require!(config.fee_change_eta != 0, CustomError::NoPendingFeeChange);
+        require!(
+            Clock::get()?.unix_timestamp >= config.fee_change_eta,
+            CustomError::FeeChangeTimelocked
+        );
+
+        let old = config.platform_fee;
+        config.platform_fee = config.pending_platform_fee;
+        config.pending_platform_fee = 0;
+        config.fee_change_eta = 0;
+
+        emit!(FeeChanged {
+            old,
+            new: config.platform_fee,
+        });
+
+        Ok(())
+    }
+
+    /// Upper bound, in bps of the platform fee, for `OpenOrderParams::referral_bps`.
+    pub fn set_max_referral_bps(ctx: Context<SetConfigField>, max_referral_bps: u16) -> Result<()> {
+        require!(max_referral_bps <= 10000, CustomError::InvalidParameter);
+
+        let config = &mut ctx.accounts.global_config;
+        require!(
+            config.is_fee_manager(&ctx.accounts.authority.key()),
+            CustomError::Unauthorized
+        );
+        let old = config.max_referral_bps;
+        config.max_referral_bps = max_referral_bps;
+
+        emit!(MaxReferralBpsChanged {
+            old,
+            new: max_referral_bps,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_treasury(ctx: Context<SetConfigField>, treasury: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        require!(
+            config.is_treasury_manager(&ctx.accounts.authority.key()),
+            CustomError::Unauthorized
+        );
+        let old = config.treasury;
+        config.treasury = treasury;
+
+        emit!(TreasuryChanged { old, new: treasury });
+
+        Ok(())
+    }
+
+    pub fn add_executor(ctx: Context<AddExecutor>, executor: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.executor_registry;
+        require!(
+            !registry.executors.contains(&executor),
+            CustomError::ExecutorAlreadyExists
+        );
+        require!(
+            registry.executors.len() < ExecutorRegistry::MAX_EXECUTORS,
+            CustomError::ExecutorRegistryFull
+        );
+
+        registry.bump = ctx.bumps.executor_registry;
+        registry.executors.push(executor);
+
+        emit!(ExecutorAdded { executor });
+
+        Ok(())
+    }
+
+    pub fn remove_executor(ctx: Context<RemoveExecutor>, executor: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.executor_registry;
+        let index = registry
+            .executors
+            .iter()
+            .position(|key| *key == executor)
+            .ok_or(CustomError::ExecutorNotFound)?;
+        registry.executors.swap_remove(index);
+
+        emit!(ExecutorRemoved { executor });
+
+        Ok(())
+    }
+
+    /// Approves `target` to receive slices bound for `to_chain_id`: the
+    /// `target_sol` account for SOL orders, the token account owner for SPL.
+    pub fn add_bridge_target(
+        ctx: Context<AddBridgeTarget>,
+        to_chain_id: u64,
+        target: Pubkey,
+    ) -> Result<()> {
+        require!(
+            to_chain_id != 0 && to_chain_id != SOLANA_CHAIN_ID,
+            CustomError::InvalidParameter
+        );
+
+        let bridge_target = &mut ctx.accounts.bridge_target;
+        require!(
+            !bridge_target.targets.contains(&target),
+            CustomError::BridgeTargetAlreadyExists
+        );
+        require!(
+            bridge_target.targets.len() < BridgeTarget::MAX_TARGETS,
+            CustomError::BridgeTargetRegistryFull
+        );
+
+        bridge_target.to_chain_id = to_chain_id;
+        bridge_target.bump = ctx.bumps.bridge_target;
+        bridge_target.targets.push(target);
+
+        emit!(BridgeTargetAdded {
+            to_chain_id,
+            target,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_bridge_target(
+        ctx: Context<RemoveBridgeTarget>,
+        to_chain_id: u64,
+        target: Pubkey,
+    ) -> Result<()> {
+        let bridge_target = &mut ctx.accounts.bridge_target;
+        let index = bridge_target
+            .targets
+            .iter()
+            .position(|key| *key == target)
+            .ok_or(CustomError::BridgeTargetNotFound)?;
+        bridge_target.targets.swap_remove(index);
+
+        emit!(BridgeTargetRemoved {
+            to_chain_id,
+            target,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_swap_program(ctx: Context<AddSwapProgram>, program: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.swap_program_registry;
+        require!(
+            !registry.programs.contains(&program),
+            CustomError::SwapProgramAlreadyExists
+        );
+        require!(
+            registry.programs.len() < SwapProgramRegistry::MAX_SWAP_PROGRAMS,
+            CustomError::SwapProgramRegistryFull
+        );
+
+        registry.bump = ctx.bumps.swap_program_registry;
+        registry.programs.push(program);
+
+        emit!(SwapProgramAdded { program });
+
+        Ok(())
+    }
+
+    pub fn remove_swap_program(ctx: Context<RemoveSwapProgram>, program: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.swap_program_registry;
+        let index = registry
+            .programs
+            .iter()
+            .position(|key| *key == program)
+            .ok_or(CustomError::SwapProgramNotFound)?;
+        registry.programs.swap_remove(index);
+
+        emit!(SwapProgramRemoved { program });
+
+        Ok(())
+    }
+
+    /// `Pubkey::default()` as `from_token` or `0` as `to_chain_id` acts as a
+    /// wildcard; see `resolve_platform_fee` for precedence.
+    pub fn set_fee_tier(
+        ctx: Context<SetFeeTier>,
+        from_token: Pubkey,
+        to_chain_id: u64,
+        platform_fee: u16,
+    ) -> Result<()> {
+        require!(
+            from_token != Pubkey::default() || to_chain_id != 0,
+            CustomError::InvalidParameter
+        );
+        require!(
+            platform_fee <= MAX_PLATFORM_FEE,
+            CustomError::InvalidPlatformFee
+        );
+
+        let fee_tier = &mut ctx.accounts.fee_tier;
+        fee_tier.from_token = from_token;
+        fee_tier.to_chain_id = to_chain_id;
+        fee_tier.platform_fee = platform_fee;
+        fee_tier.bump = ctx.bumps.fee_tier;
+
+        emit!(FeeTierSet {
+            from_token,
+            to_chain_id,
+            platform_fee,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_fee_tier(
+        _ctx: Context<RemoveFeeTier>,
+        from_token: Pubkey,
+        to_chain_id: u64,
+    ) -> Result<()> {
+        emit!(FeeTierRemoved {
+            from_token,
+            to_chain_id,
+        });
+
+        Ok(())
+    }
+
+    /// Registers the oracle that gates execution for one route. Once a feed
+    /// exists, every slice on the route must come with a fresh price update.
+    pub fn set_price_feed(ctx: Context<SetPriceFeed>, params: PriceFeedParams) -> Result<()> {
+        require!(
+            params.max_staleness > 0
+                && params.max_confidence_bps <= 10000
+                && params.max_deviation_bps <= 10000,
+            CustomError::InvalidParameter
+        );
+
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.from_token = params.from_token;
+        price_feed.to_chain_id = params.to_chain_id;
+        price_feed.to_token = params.to_token;
+        price_feed.feed_id = params.feed_id;
+        price_feed.from_decimals = params.from_decimals;
+        price_feed.to_decimals = params.to_decimals;
+        price_feed.max_staleness = params.max_staleness;
+        price_feed.max_confidence_bps = params.max_confidence_bps;
+        price_feed.max_deviation_bps = params.max_deviation_bps;
+        price_feed.bump = ctx.bumps.price_feed;
+
+        emit!(PriceFeedSet {
+            from_token: params.from_token,
+            to_chain_id: params.to_chain_id,
+            to_token: params.to_token,
+            feed_id: params.feed_id,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_price_feed(
+        _ctx: Context<RemovePriceFeed>,
+        from_token: Pubkey,
+        to_chain_id: u64,
+        to_token: [u8; 32],
+    ) -> Result<()> {
+        emit!(PriceFeedRemoved {
+            from_token,
+            to_chain_id,
+            to_token,
+        });
+
+        Ok(())
+    }
+
     pub fn open_order_sol(ctx: Context<OpenOrderSol>, params: OpenOrderParams) -> Result<()> {
         if params.from_token != native_token()
-            || params.from_chain_id != 10002
+            || params.from_chain_id != SOLANA_CHAIN_ID
             || params.amount_in == 0
             || params.to_chain_id == 0
             || params.to_token == [0u8; 32]
-            || params.recipient == [0u8; 32]
             || params.execute_count == 0
-            || params.time_interval == 0
-            || params.amount_out_min == [0u8; 32]
-            || params.amount_out_max == [0u8; 32]
+            || params.amount_in < params.execute_count as u64
+            || !valid_order_terms(
+                &params.amount_out_min,
+                &params.amount_out_max,
+                params.time_interval,
+                &params.recipient,
+            )
         {
             return Err(error!(CustomError::InvalidParameter));
         }
@@ -77,6 +536,10 @@ pub mod interra_dca_order_solana {
             !ctx.accounts.global_config.paused,
             CustomError::ProgramPaused
         );
+        require!(
+            valid_referral(&params, &ctx.accounts.global_config),
+            CustomError::InvalidReferral
+        );
 
         require!(
             ctx.accounts.user.lamports() > params.amount_in,
@@ -94,13 +557,40 @@ pub mod interra_dca_order_solana {
             params.amount_in, // lamports
         )?;
 
+        let nonce = register_order(
+            &mut ctx.accounts.user_state,
+            ctx.accounts.user.key(),
+            ctx.accounts.order.key(),
+            ctx.bumps.user_state,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
         let order = &mut ctx.accounts.order;
 
-        add_order(order, &ctx.accounts.user, &params, ctx.bumps.order);
+        add_order(
+            order,
+            &ctx.accounts.user,
+            &params,
+            ctx.bumps.order,
+            nonce,
+            now,
+            resolve_platform_fee(
+                ctx.accounts.global_config.platform_fee,
+                [
+                    &ctx.accounts.mint_chain_fee_tier,
+                    &ctx.accounts.mint_fee_tier,
+                    &ctx.accounts.chain_fee_tier,
+                ],
+            )?,
+        );
+        check_slice_costs(&ctx.accounts.order)?;
 
-        emit!(OrderOpened {
-            order_pubkey: ctx.accounts.order.key(),
-        });
+        emit!(order_opened(&ctx.accounts.order));
+        ctx.accounts
+            .protocol_stats
+            .order_opened(ctx.bumps.protocol_stats);
+        ctx.accounts
+            .mint_stats
+            .order_opened(params.from_token, ctx.bumps.mint_stats);
 
         Ok(())
     }
@@ -111,18 +601,25 @@ pub mod interra_dca_order_solana {
             CustomError::ProgramPaused
         );
         if params.from_token == native_token()
-            || params.from_chain_id != 10002
+            || params.from_chain_id != SOLANA_CHAIN_ID
             || params.amount_in == 0
             || params.to_chain_id == 0
             || params.to_token == [0u8; 32]
-            || params.recipient == [0u8; 32]
             || params.execute_count == 0
-            || params.time_interval == 0
-            || params.amount_out_min == [0u8; 32]
-            || params.amount_out_max == [0u8; 32]
+            || params.amount_in < params.execute_count as u64
+            || !valid_order_terms(
+                &params.amount_out_min,
+                &params.amount_out_max,
+                params.time_interval,
+                &params.recipient,
+            )
         {
             return Err(error!(CustomError::InvalidParameter));
         }
+        require!(
+            valid_referral(&params, &ctx.accounts.global_config),
+            CustomError::InvalidReferral
+        );
         require_keys_eq!(
             ctx.accounts.token_mint.key(),
             params.from_token,
@@ -134,33 +631,88 @@ pub mod interra_dca_order_solana {
             CustomError::InvalidParameter
         );
 
+        let balance_before = ctx.accounts.order_token_account.amount;
+
         // Transfer SPL token to order_token_account
-        let cpi_accounts = TokenTransfer {
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.user_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
             to: ctx.accounts.order_token_account.to_account_info(),
             authority: ctx.accounts.user.to_account_info(),
         };
         let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
 
         // Transfer SPL tokens
-        token::transfer(cpi_ctx, params.amount_in)?;
+        token_interface::transfer_checked(
+            cpi_ctx,
+            params.amount_in,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        // Token-2022 transfer fees are withheld from the vault, and a vault
+        // created ahead of the order may hold a balance already, so escrow
+        // only what this transfer added.
+        ctx.accounts.order_token_account.reload()?;
+        let received = ctx
+            .accounts
+            .order_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(CustomError::Overflow)?;
+        require!(
+            received >= params.execute_count as u64,
+            CustomError::InsufficientFunds
+        );
+        let params = OpenOrderParams {
+            amount_in: received,
+            ..params
+        };
 
+        let nonce = register_order(
+            &mut ctx.accounts.user_state,
+            ctx.accounts.user.key(),
+            ctx.accounts.order.key(),
+            ctx.bumps.user_state,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
         let order = &mut ctx.accounts.order;
-        add_order(order, &ctx.accounts.user, &params, ctx.bumps.order);
+        add_order(
+            order,
+            &ctx.accounts.user,
+            &params,
+            ctx.bumps.order,
+            nonce,
+            now,
+            resolve_platform_fee(
+                ctx.accounts.global_config.platform_fee,
+                [
+                    &ctx.accounts.mint_chain_fee_tier,
+                    &ctx.accounts.mint_fee_tier,
+                    &ctx.accounts.chain_fee_tier,
+                ],
+            )?,
+        );
+        check_slice_costs(&ctx.accounts.order)?;
 
-        emit!(OrderOpened {
-            order_pubkey: ctx.accounts.order.key(),
-        });
+        emit!(order_opened(&ctx.accounts.order));
+        ctx.accounts
+            .protocol_stats
+            .order_opened(ctx.bumps.protocol_stats);
+        ctx.accounts
+            .mint_stats
+            .order_opened(params.from_token, ctx.bumps.mint_stats);
         Ok(())
     }
 
     pub fn cancel_order_sol(ctx: Context<CancelOrderSol>) -> Result<()> {
+        // Pause only stops new activity; senders can still exit unless frozen.
         require!(
-            ctx.accounts.global_config.paused == false,
-            CustomError::ProgramPaused
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
         );
 
         let order = &ctx.accounts.order;
+        require!(order.is_open(), CustomError::OrderNotActive);
         require!(
             order.from_token == native_token(),
             CustomError::InvalidParameter
@@ -186,21 +738,39 @@ pub mod interra_dca_order_solana {
             .to_account_info()
             .try_borrow_mut_lamports()? += order.remaining_amount;
 
+        let now = Clock::get()?.unix_timestamp;
+        let refunded_amount = ctx.accounts.order.remaining_amount;
+        let status = if ctx.accounts.order.is_overdue(now) {
+            OrderStatus::Expired
+        } else {
+            OrderStatus::Cancelled
+        };
+
         emit!(OrderCancelled {
             order_pubkey: ctx.accounts.order.key(),
             by: ctx.accounts.user.key(),
+            refunded_amount,
+            status,
         });
+        retire_order(&mut ctx.accounts.user_state, ctx.accounts.order.key(), true);
+        ctx.accounts.protocol_stats.order_cancelled();
+        ctx.accounts.mint_stats.order_cancelled(refunded_amount);
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount = 0;
+        settle_order(order, status, now);
 
         Ok(())
     }
 
     pub fn cancel_order_spl(ctx: Context<CancelOrderSpl>) -> Result<()> {
         require!(
-            ctx.accounts.global_config.paused == false,
-            CustomError::ProgramPaused
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
         );
 
         let order = &ctx.accounts.order;
+        require!(order.is_open(), CustomError::OrderNotActive);
         require!(
             order.from_token != native_token(),
             CustomError::InvalidParameter
@@ -221,14 +791,15 @@ pub mod interra_dca_order_solana {
         let seeds = &[
             b"dca_order",
             order.sender.as_ref(),
-            &order.time_stamp.to_le_bytes(),
+            &order.nonce.to_le_bytes(),
             &[order.bump],
         ];
         let signer = &[&seeds[..]];
 
         // SPL Token Transfer（从 PDA 转 token 到用户）
-        let cpi_accounts = TokenTransfer {
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.order_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
             to: ctx.accounts.user_token_account.to_account_info(),
             authority: ctx.accounts.order.to_account_info(), // PDA 授权
         };
@@ -237,341 +808,2807 @@ pub mod interra_dca_order_solana {
             cpi_accounts,
             signer,
         );
-        token::transfer(cpi_ctx, order.remaining_amount)?;
-
-        let close_cpi_accounts = CloseAccount {
-            account: ctx.accounts.order_token_account.to_account_info(),
-            destination: ctx.accounts.refund_receiver.to_account_info(),
-            authority: ctx.accounts.order.to_account_info(),
-        };
+        // Refund the whole vault, not just `remaining_amount`: tokens sent to
+        // it outside the order would otherwise keep it from closing.
+        let refunded_amount = ctx.accounts.order_token_account.amount;
+        token_interface::transfer_checked(
+            cpi_ctx,
+            refunded_amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
 
-        let close_cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            close_cpi_accounts,
+        close_order_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.order_token_account.to_account_info(),
+            ctx.accounts.refund_receiver.to_account_info(),
+            ctx.accounts.order.to_account_info(),
             signer,
-        );
+        )?;
 
-        token::close_account(close_cpi_ctx)?;
+        let now = Clock::get()?.unix_timestamp;
+        let status = if ctx.accounts.order.is_overdue(now) {
+            OrderStatus::Expired
+        } else {
+            OrderStatus::Cancelled
+        };
 
         emit!(OrderCancelled {
             order_pubkey: ctx.accounts.order.key(),
             by: ctx.accounts.user.key(),
+            refunded_amount,
+            status,
         });
+        retire_order(&mut ctx.accounts.user_state, ctx.accounts.order.key(), true);
+        ctx.accounts.protocol_stats.order_cancelled();
+        ctx.accounts.mint_stats.order_cancelled(refunded_amount);
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount = 0;
+        settle_order(order, status, now);
 
         Ok(())
     }
 
-    pub fn execute_order_sol(
-        ctx: Context<ExecuteOrderSol>,
-        execute_amount: u64,
-        native_token_volume: u64,
-    ) -> Result<()> {
-        let config = &ctx.accounts.global_config;
-
+    /// Refunds and closes a SOL order opened by the baseline deployment,
+    /// whose layout and seeds `cancel_order_sol` no longer accepts. Such
+    /// orders predate the stats accounts, so those are left untouched.
+    pub fn cancel_legacy_order_sol(ctx: Context<CancelLegacyOrderSol>) -> Result<()> {
         require!(
-            ctx.accounts.global_config.paused == false,
-            CustomError::ProgramPaused
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
         );
 
+        let order = load_legacy_order(&ctx.accounts.order)?;
         require!(
-            ctx.accounts.order.from_token != native_token(),
+            order.from_token == native_token(),
             CustomError::InvalidParameter
         );
-
         require!(
-            ctx.accounts.order.remaining_amount < execute_amount,
-            CustomError::InvalidParameter
-        );
-
-        require_keys_eq!(
-            ctx.accounts.executor.key(),
-            ctx.accounts.global_config.owner,
-            CustomError::OnlyOwnerCanExecute
+            ctx.accounts.user.key() == order.sender
+                || ctx.accounts.user.key() == ctx.accounts.global_config.owner,
+            CustomError::OnlySenderOrOwner
         );
-
-        require_keys_eq!(
-            ctx.accounts.refund_receiver.key(),
-            ctx.accounts.order.sender,
+        require!(
+            ctx.accounts.refund_receiver.key() == order.sender,
             CustomError::InvalidRefundReceiver
         );
 
-        let platform_fee = config.platform_fee;
-
-        let fee_amount = execute_amount
-            .checked_mul(platform_fee as u64)
-            .ok_or(CustomError::Overflow)?
-            / 10000;
-        let send_amount = execute_amount
-            .checked_sub(fee_amount)
-            .ok_or(CustomError::Overflow)?;
-        require!(send_amount > 0, CustomError::InsufficientFunds);
-
-        **ctx
-            .accounts
-            .order
-            .to_account_info()
-            .try_borrow_mut_lamports()? -= execute_amount;
-        **ctx.accounts.target_sol.try_borrow_mut_lamports()? += send_amount;
-        **ctx.accounts.treasury.try_borrow_mut_lamports()? += fee_amount;
+        // The escrow and the rent go back together.
+        close_legacy_order(
+            ctx.accounts.order.to_account_info(),
+            ctx.accounts.refund_receiver.to_account_info(),
+        )?;
 
-        emit!(OrderExecuted {
+        emit!(OrderCancelled {
             order_pubkey: ctx.accounts.order.key(),
-            by: ctx.accounts.executor.key(),
-            native_token_volume,
+            by: ctx.accounts.user.key(),
+            refunded_amount: order.remaining_amount,
+            status: OrderStatus::Cancelled,
         });
 
-        if ctx.accounts.order.remaining_amount == execute_amount
-            || ctx.accounts.order.remaining_amount == 1
-        {
-            ctx.accounts
-                .order
-                .close(ctx.accounts.refund_receiver.to_account_info())?;
-        } else {
-            ctx.accounts.order.remaining_amount -= execute_amount;
-            ctx.accounts.order.remaining_execute_count -= 1;
-        }
+        Ok(())
+    }
+
+    /// SPL counterpart of `cancel_legacy_order_sol`.
+    pub fn cancel_legacy_order_spl(ctx: Context<CancelLegacyOrderSpl>) -> Result<()> {
+        require!(
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
+        );
+
+        let order = load_legacy_order(&ctx.accounts.order)?;
+        require!(
+            order.from_token != native_token() && order.from_token == ctx.accounts.token_mint.key(),
+            CustomError::InvalidParameter
+        );
+        require!(
+            ctx.accounts.user.key() == order.sender
+                || ctx.accounts.user.key() == ctx.accounts.global_config.owner,
+            CustomError::OnlySenderOrOwner
+        );
+        require!(
+            ctx.accounts.refund_receiver.key() == order.sender,
+            CustomError::InvalidRefundReceiver
+        );
+
+        let seeds = &[
+            b"dca_order",
+            order.sender.as_ref(),
+            &order.time_stamp.to_le_bytes(),
+            &[order.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let refunded_amount = ctx.accounts.order_token_account.amount;
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.order_token_account.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.order.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(
+            cpi_ctx,
+            refunded_amount,
+            ctx.accounts.token_mint.decimals,
+        )?;
+
+        close_order_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.order_token_account.to_account_info(),
+            ctx.accounts.refund_receiver.to_account_info(),
+            ctx.accounts.order.to_account_info(),
+            signer,
+        )?;
+        close_legacy_order(
+            ctx.accounts.order.to_account_info(),
+            ctx.accounts.refund_receiver.to_account_info(),
+        )?;
+
+        emit!(OrderCancelled {
+            order_pubkey: ctx.accounts.order.key(),
+            by: ctx.accounts.user.key(),
+            refunded_amount,
+            status: OrderStatus::Cancelled,
+        });
 
         Ok(())
     }
 
-    pub fn execute_order_spl(
-        ctx: Context<ExecuteOrderSpl>,
-        execute_amount: u64,
-        native_token_volume: u64,
+    pub fn deposit_to_order_sol(
+        ctx: Context<DepositToOrderSol>,
+        amount: u64,
+        remaining_execute_count: Option<u16>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.global_config.paused,
+            CustomError::ProgramPaused
+        );
+        require!(amount > 0, CustomError::NeedsMoreThanZero);
+        require!(ctx.accounts.order.is_open(), CustomError::OrderNotActive);
+        require!(
+            ctx.accounts.order.from_token == native_token(),
+            CustomError::InvalidParameter
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.order.to_account_info(),
+                },
+            ),
+            amount, // lamports
+        )?;
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount = order
+            .remaining_amount
+            .checked_add(amount)
+            .ok_or(CustomError::Overflow)?;
+        rebase_slices(order, remaining_execute_count)?;
+
+        emit!(OrderDeposited {
+            order_pubkey: order.key(),
+            amount,
+            remaining_amount: order.remaining_amount,
+            remaining_execute_count: order.remaining_execute_count,
+        });
+
+        Ok(())
+    }
+
+    pub fn deposit_to_order_spl(
+        ctx: Context<DepositToOrderSpl>,
+        amount: u64,
+        remaining_execute_count: Option<u16>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.global_config.paused,
+            CustomError::ProgramPaused
+        );
+        require!(amount > 0, CustomError::NeedsMoreThanZero);
+        require!(ctx.accounts.order.is_open(), CustomError::OrderNotActive);
+        require!(
+            ctx.accounts.order.from_token != native_token(),
+            CustomError::InvalidParameter
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+
+        let balance_before = ctx.accounts.order_token_account.amount;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.user_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.order_token_account.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        // Same as open_order_spl: only count what landed after transfer fees.
+        ctx.accounts.order_token_account.reload()?;
+        let received = ctx
+            .accounts
+            .order_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(CustomError::Overflow)?;
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount = order
+            .remaining_amount
+            .checked_add(received)
+            .ok_or(CustomError::Overflow)?;
+        rebase_slices(order, remaining_execute_count)?;
+
+        emit!(OrderDeposited {
+            order_pubkey: order.key(),
+            amount: received,
+            remaining_amount: order.remaining_amount,
+            remaining_execute_count: order.remaining_execute_count,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_from_order_sol(
+        ctx: Context<WithdrawFromOrderSol>,
+        amount: u64,
+        remaining_execute_count: Option<u16>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
+        );
+        require!(amount > 0, CustomError::NeedsMoreThanZero);
+        require!(ctx.accounts.order.is_open(), CustomError::OrderNotActive);
+        require!(
+            ctx.accounts.order.from_token == native_token(),
+            CustomError::InvalidParameter
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+        // Withdrawing everything is what cancel_order_sol is for.
+        require!(
+            amount < ctx.accounts.order.remaining_amount,
+            CustomError::InsufficientFunds
+        );
+
+        **ctx
+            .accounts
+            .order
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .user
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount -= amount;
+        rebase_slices(order, remaining_execute_count)?;
+
+        emit!(OrderWithdrawn {
+            order_pubkey: order.key(),
+            amount,
+            remaining_amount: order.remaining_amount,
+            remaining_execute_count: order.remaining_execute_count,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_from_order_spl(
+        ctx: Context<WithdrawFromOrderSpl>,
+        amount: u64,
+        remaining_execute_count: Option<u16>,
     ) -> Result<()> {
-        let config = &ctx.accounts.global_config;
+        require!(
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
+        );
+        require!(amount > 0, CustomError::NeedsMoreThanZero);
+
         let order = &ctx.accounts.order;
+        require!(order.is_open(), CustomError::OrderNotActive);
+        require!(
+            order.from_token != native_token(),
+            CustomError::InvalidParameter
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            order.sender,
+            CustomError::OnlySender
+        );
+        // Withdrawing everything is what cancel_order_spl is for.
+        require!(
+            amount < order.remaining_amount,
+            CustomError::InsufficientFunds
+        );
+
+        // PDA 签名 seeds
+        let seeds = &[
+            b"dca_order",
+            order.sender.as_ref(),
+            &order.nonce.to_le_bytes(),
+            &[order.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        // SPL Token Transfer（从 PDA 转 token 到用户）
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.order_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: ctx.accounts.order.to_account_info(), // PDA 授权
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        let order = &mut ctx.accounts.order;
+        order.remaining_amount -= amount;
+        rebase_slices(order, remaining_execute_count)?;
 
+        emit!(OrderWithdrawn {
+            order_pubkey: order.key(),
+            amount,
+            remaining_amount: order.remaining_amount,
+            remaining_execute_count: order.remaining_execute_count,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_order(ctx: Context<UpdateOrder>, params: UpdateOrderParams) -> Result<()> {
         require!(
-            ctx.accounts.global_config.paused == false,
+            !ctx.accounts.global_config.paused,
             CustomError::ProgramPaused
         );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+        require!(ctx.accounts.order.is_open(), CustomError::OrderNotActive);
+        require!(
+            valid_order_terms(
+                &params.amount_out_min,
+                &params.amount_out_max,
+                params.time_interval,
+                &params.recipient,
+            ),
+            CustomError::InvalidParameter
+        );
+
+        let order = &mut ctx.accounts.order;
+        order.amount_out_min = params.amount_out_min;
+        order.amount_out_max = params.amount_out_max;
+        order.recipient = params.recipient;
+        if order.time_interval != params.time_interval {
+            order.time_interval = params.time_interval;
+            // Re-anchor the pending slice on the new interval.
+            if order.last_executed_at != 0 {
+                let last_executed_at = order.last_executed_at;
+                schedule_next_execution(order, last_executed_at)?;
+            }
+        }
 
+        emit!(OrderUpdated {
+            order_pubkey: order.key(),
+            amount_out_min: order.amount_out_min,
+            amount_out_max: order.amount_out_max,
+            time_interval: order.time_interval,
+            recipient: order.recipient,
+        });
+
+        Ok(())
+    }
+
+    /// Stops executors from filling the order until the sender resumes it.
+    pub fn pause_order(ctx: Context<UpdateOrder>) -> Result<()> {
         require!(
-            ctx.accounts.order.from_token != native_token(),
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+        require!(
+            ctx.accounts.order.status == OrderStatus::Active,
+            CustomError::OrderNotActive
+        );
+
+        ctx.accounts.order.status = OrderStatus::Paused;
+
+        emit!(OrderPaused {
+            order_pubkey: ctx.accounts.order.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Reactivates a paused order. Intervals missed while paused are skipped,
+    /// so the next slice lands on the order's `time_interval` schedule.
+    pub fn resume_order(ctx: Context<UpdateOrder>) -> Result<()> {
+        require!(
+            !ctx.accounts.global_config.frozen,
+            CustomError::ProgramFrozen
+        );
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.order.sender,
+            CustomError::OnlySender
+        );
+        require!(
+            ctx.accounts.order.status == OrderStatus::Paused,
+            CustomError::OrderNotPaused
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let order = &mut ctx.accounts.order;
+        resume_schedule(order, now)?;
+        order.status = OrderStatus::Active;
+
+        emit!(OrderResumed {
+            order_pubkey: order.key(),
+            next_execution_at: order.next_execution_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn execute_order_sol(
+        ctx: Context<ExecuteOrderSol>,
+        native_token_volume: u64,
+        amount_out: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let mut split = begin_slice(
+            &ctx.accounts.global_config,
+            &ctx.accounts.executor_registry,
+            &ctx.accounts.executor.key(),
+            &ctx.accounts.order,
+            [
+                &ctx.accounts.mint_chain_fee_tier,
+                &ctx.accounts.mint_fee_tier,
+                &ctx.accounts.chain_fee_tier,
+            ],
+            now,
+        )?;
+
+        require!(
+            ctx.accounts.order.from_token == native_token(),
             CustomError::InvalidParameter
         );
 
-        require!(
-            ctx.accounts.order.remaining_amount < execute_amount,
-            CustomError::InvalidParameter
-        );
+        require!(
+            ctx.accounts
+                .bridge_target
+                .targets
+                .contains(&ctx.accounts.target_sol.key()),
+            CustomError::UnapprovedTarget
+        );
+
+        record_receipt(
+            &mut ctx.accounts.receipt,
+            &ctx.accounts.order,
+            ctx.accounts.executor.key(),
+            &split,
+            amount_out,
+            now,
+            ctx.bumps.receipt,
+        )?;
+        check_oracle_quote(
+            &ctx.accounts.price_feed,
+            ctx.accounts.price_update.as_ref(),
+            split.send_amount,
+            &amount_out,
+            now,
+        )?;
+
+        **ctx
+            .accounts
+            .order
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= split.execute_amount;
+        **ctx.accounts.target_sol.try_borrow_mut_lamports()? += split.send_amount;
+        **ctx
+            .accounts
+            .executor
+            .to_account_info()
+            .try_borrow_mut_lamports()? += split.executor_tip;
+        if split.referral_amount > 0 {
+            let referrer = ctx
+                .accounts
+                .referrer
+                .as_ref()
+                .ok_or(CustomError::MissingReferrerAccount)?;
+            if can_receive_lamports(referrer, split.referral_amount)? {
+                **referrer.try_borrow_mut_lamports()? += split.referral_amount;
+            } else {
+                // Crediting it would fail the transaction, and with it every
+                // later slice, so the treasury keeps the share instead.
+                split.fee_amount += split.referral_amount;
+                split.referral_amount = 0;
+            }
+        }
+        **ctx.accounts.treasury.try_borrow_mut_lamports()? += split.fee_amount;
+
+        let fill = SliceFill {
+            executor: ctx.accounts.executor.key(),
+            native_token_volume,
+            amount_out,
+        };
+        finish_slice(
+            &mut ctx.accounts.order,
+            &mut ctx.accounts.user_state,
+            &mut ctx.accounts.protocol_stats,
+            &mut ctx.accounts.mint_stats,
+            &split,
+            &fill,
+        )
+    }
+
+    pub fn execute_order_spl(
+        ctx: Context<ExecuteOrderSpl>,
+        native_token_volume: u64,
+        amount_out: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let split = begin_slice(
+            &ctx.accounts.global_config,
+            &ctx.accounts.executor_registry,
+            &ctx.accounts.executor.key(),
+            &ctx.accounts.order,
+            [
+                &ctx.accounts.mint_chain_fee_tier,
+                &ctx.accounts.mint_fee_tier,
+                &ctx.accounts.chain_fee_tier,
+            ],
+            now,
+        )?;
+        let order = &ctx.accounts.order;
+
+        require!(
+            order.from_token != native_token(),
+            CustomError::InvalidParameter
+        );
+
+        require_keys_eq!(
+            ctx.accounts.refund_receiver.key(),
+            order.sender,
+            CustomError::InvalidRefundReceiver
+        );
+
+        require!(
+            ctx.accounts
+                .bridge_target
+                .targets
+                .contains(&ctx.accounts.target_token_account.owner),
+            CustomError::UnapprovedTarget
+        );
+
+        let is_final_slice = order.remaining_execute_count <= 1;
+        record_receipt(
+            &mut ctx.accounts.receipt,
+            order,
+            ctx.accounts.executor.key(),
+            &split,
+            amount_out,
+            now,
+            ctx.bumps.receipt,
+        )?;
+        check_oracle_quote(
+            &ctx.accounts.price_feed,
+            ctx.accounts.price_update.as_ref(),
+            split.send_amount,
+            &amount_out,
+            now,
+        )?;
+
+        // PDA 签名 seeds
+        let seeds = &[
+            b"dca_order",
+            order.sender.as_ref(),
+            &order.nonce.to_le_bytes(),
+            &[order.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let accounts = &ctx.accounts;
+        let vault = OrderVault {
+            token_program: &accounts.token_program,
+            token_mint: &accounts.token_mint,
+            vault: accounts.order_token_account.to_account_info(),
+            order: accounts.order.to_account_info(),
+        };
+        // SPL Token Transfer（从 PDA 转 token 到 target）
+        vault.pay(
+            accounts.target_token_account.to_account_info(),
+            split.send_amount,
+            signer,
+        )?;
+        vault.pay_slice_costs(
+            &split,
+            accounts.treasury_token_account.to_account_info(),
+            accounts
+                .executor_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            accounts
+                .referrer_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            signer,
+        )?;
+
+        // The vault is only emptied by the final slice.
+        if is_final_slice {
+            vault.sweep(
+                accounts
+                    .refund_token_account
+                    .as_ref()
+                    .map(|account| account.to_account_info()),
+                signer,
+            )?;
+            close_order_vault(
+                &accounts.token_program,
+                &accounts.token_mint,
+                accounts.order_token_account.to_account_info(),
+                accounts.refund_receiver.to_account_info(),
+                accounts.order.to_account_info(),
+                signer,
+            )?;
+        }
+
+        let fill = SliceFill {
+            executor: ctx.accounts.executor.key(),
+            native_token_volume,
+            amount_out,
+        };
+        finish_slice(
+            &mut ctx.accounts.order,
+            &mut ctx.accounts.user_state,
+            &mut ctx.accounts.protocol_stats,
+            &mut ctx.accounts.mint_stats,
+            &split,
+            &fill,
+        )
+    }
+
+    /// Same-chain execution: swaps the slice through a whitelisted swap program
+    /// instead of sending it to a bridge target. `swap_data` and the remaining
+    /// accounts form the swap instruction; the order PDA signs for the vault.
+    /// The swap must spend exactly the slice's `send_amount`, and what lands in
+    /// the recipient's `to_token` account is checked like a quoted `amount_out`.
+    ///
+    /// Native SOL orders have no token vault, so each slice is wrapped into a
+    /// WSOL vault that the executor funds and gets back once the slice is done.
+    /// Their fee, tip and referral share are then paid in WSOL.
+    pub fn execute_order_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteOrderSwap<'info>>,
+        swap_data: Vec<u8>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let split = begin_slice(
+            &ctx.accounts.global_config,
+            &ctx.accounts.executor_registry,
+            &ctx.accounts.executor.key(),
+            &ctx.accounts.order,
+            [
+                &ctx.accounts.mint_chain_fee_tier,
+                &ctx.accounts.mint_fee_tier,
+                &ctx.accounts.chain_fee_tier,
+            ],
+            now,
+        )?;
+        let order = &ctx.accounts.order;
+
+        require!(
+            order.to_chain_id == SOLANA_CHAIN_ID,
+            CustomError::InvalidParameter
+        );
+
+        require!(
+            ctx.accounts
+                .swap_program_registry
+                .programs
+                .contains(&ctx.accounts.swap_program.key()),
+            CustomError::SwapProgramNotAllowed
+        );
+
+        require_keys_eq!(
+            ctx.accounts.refund_receiver.key(),
+            order.sender,
+            CustomError::InvalidRefundReceiver
+        );
+
+        let is_native = order.from_token == native_token();
+        let is_final_slice = order.remaining_execute_count <= 1;
+
+        // PDA 签名 seeds
+        let order_key = order.key();
+        let seeds = &[
+            b"dca_order",
+            order.sender.as_ref(),
+            &order.nonce.to_le_bytes(),
+            &[order.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let accounts = &ctx.accounts;
+        let vault = OrderVault {
+            token_program: &accounts.token_program,
+            token_mint: &accounts.token_mint,
+            vault: accounts.order_token_account.to_account_info(),
+            order: accounts.order.to_account_info(),
+        };
+        if is_native {
+            **vault.order.try_borrow_mut_lamports()? -= split.execute_amount;
+            **vault.vault.try_borrow_mut_lamports()? += split.execute_amount;
+            token_interface::sync_native(CpiContext::new(
+                accounts.token_program.to_account_info(),
+                SyncNative {
+                    account: vault.vault.clone(),
+                },
+            ))?;
+        }
+        vault.pay_slice_costs(
+            &split,
+            accounts.treasury_token_account.to_account_info(),
+            accounts
+                .executor_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            accounts
+                .referrer_token_account
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            signer,
+        )?;
+
+        ctx.accounts.order_token_account.reload()?;
+        let vault_before = ctx.accounts.order_token_account.amount;
+        let received_before = ctx.accounts.recipient_token_account.amount;
+
+        // 调用 swap program，order PDA 作为 vault 的签名者
+        let swap_ix = Instruction {
+            program_id: ctx.accounts.swap_program.key(),
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.is_signer || account.key() == order_key,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: swap_data,
+        };
+        invoke_signed(&swap_ix, ctx.remaining_accounts, signer)?;
+
+        // The swap may only spend the slice and must leave the vault ours.
+        let order_vault = &mut ctx.accounts.order_token_account;
+        order_vault.reload()?;
+        require!(
+            vault_before.checked_sub(order_vault.amount) == Some(split.send_amount)
+                && order_vault.owner == order_key
+                && order_vault.delegate.is_none()
+                && order_vault.close_authority.is_none(),
+            CustomError::InvalidSwapResult
+        );
+
+        ctx.accounts.recipient_token_account.reload()?;
+        let received = ctx
+            .accounts
+            .recipient_token_account
+            .amount
+            .checked_sub(received_before)
+            .ok_or(CustomError::InvalidSwapResult)?;
+        let mut amount_out = [0u8; 32];
+        amount_out[..8].copy_from_slice(&received.to_le_bytes());
+
+        record_receipt(
+            &mut ctx.accounts.receipt,
+            &ctx.accounts.order,
+            ctx.accounts.executor.key(),
+            &split,
+            amount_out,
+            now,
+            ctx.bumps.receipt,
+        )?;
+        check_oracle_quote(
+            &ctx.accounts.price_feed,
+            ctx.accounts.price_update.as_ref(),
+            split.send_amount,
+            &amount_out,
+            now,
+        )?;
+        // Delivery happened in this transaction, so the receipt is final.
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.delivered_amount = amount_out;
+        receipt.confirmed_by = crate::ID;
+        receipt.confirmed_at = now;
+
+        // A WSOL vault only lives for one slice; an SPL vault is only emptied
+        // by the final slice.
+        let accounts = &ctx.accounts;
+        if is_native || is_final_slice {
+            let destination = if is_native {
+                accounts.executor.to_account_info()
+            } else {
+                let vault = OrderVault {
+                    token_program: &accounts.token_program,
+                    token_mint: &accounts.token_mint,
+                    vault: accounts.order_token_account.to_account_info(),
+                    order: accounts.order.to_account_info(),
+                };
+                vault.sweep(
+                    accounts
+                        .refund_token_account
+                        .as_ref()
+                        .map(|account| account.to_account_info()),
+                    signer,
+                )?;
+                accounts.refund_receiver.to_account_info()
+            };
+            close_order_vault(
+                &accounts.token_program,
+                &accounts.token_mint,
+                accounts.order_token_account.to_account_info(),
+                destination,
+                accounts.order.to_account_info(),
+                signer,
+            )?;
+        }
+
+        let fill = SliceFill {
+            executor: ctx.accounts.executor.key(),
+            native_token_volume: 0,
+            amount_out,
+        };
+        finish_slice(
+            &mut ctx.accounts.order,
+            &mut ctx.accounts.user_state,
+            &mut ctx.accounts.protocol_stats,
+            &mut ctx.accounts.mint_stats,
+            &split,
+            &fill,
+        )
+    }
+
+    /// Reclaims the rent of a completed, cancelled or expired order once
+    /// `ORDER_CLOSE_GRACE_PERIOD` has passed since it settled.
+    pub fn close_order(ctx: Context<CloseOrder>) -> Result<()> {
+        let order = &ctx.accounts.order;
+        require!(
+            ctx.accounts.user.key() == order.sender
+                || ctx.accounts.user.key() == ctx.accounts.global_config.owner,
+            CustomError::OnlySenderOrOwner
+        );
+        require_keys_eq!(
+            ctx.accounts.refund_receiver.key(),
+            order.sender,
+            CustomError::InvalidRefundReceiver
+        );
+        require!(!order.is_open(), CustomError::OrderNotSettled);
+
+        let now = Clock::get()?.unix_timestamp;
+        let closable_at = order
+            .settled_at()
+            .checked_add(ORDER_CLOSE_GRACE_PERIOD)
+            .ok_or(CustomError::Overflow)?;
+        require!(now >= closable_at, CustomError::CloseGracePeriodActive);
+
+        emit!(OrderClosed {
+            order_pubkey: order.key(),
+            status: order.status,
+        });
+
+        Ok(())
+    }
+
+    /// Attests that a slice arrived on `to_chain_id`. Each receipt can only be
+    /// confirmed once, and never by the executor that filled the slice.
+    pub fn confirm_delivery(
+        ctx: Context<ConfirmDelivery>,
+        dest_tx_hash: [u8; 32],
+        delivered_amount: [u8; 32],
+    ) -> Result<()> {
+        let attester = ctx.accounts.attester.key();
+        require!(
+            attester == ctx.accounts.global_config.owner
+                || ctx.accounts.executor_registry.executors.contains(&attester),
+            CustomError::UnauthorizedExecutor
+        );
+        require!(dest_tx_hash != [0u8; 32], CustomError::InvalidParameter);
+
+        let receipt = &mut ctx.accounts.receipt;
+        require_keys_neq!(
+            attester,
+            receipt.executor,
+            CustomError::SelfAttestedDelivery
+        );
+        require!(
+            receipt.confirmed_at == 0,
+            CustomError::DeliveryAlreadyConfirmed
+        );
+
+        receipt.dest_tx_hash = dest_tx_hash;
+        receipt.delivered_amount = delivered_amount;
+        receipt.confirmed_by = attester;
+        receipt.confirmed_at = Clock::get()?.unix_timestamp;
+
+        emit!(DeliveryConfirmed {
+            receipt: receipt.key(),
+            order_pubkey: receipt.order,
+            slice_index: receipt.slice_index,
+            dest_tx_hash,
+            delivered_amount,
+            by: attester,
+        });
+
+        Ok(())
+    }
+
+    /// Returns a receipt's rent to the executor that paid it, once delivery
+    /// is confirmed and `ORDER_CLOSE_GRACE_PERIOD` has passed. Unconfirmed
+    /// receipts stay open as the record of an undelivered slice.
+    pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+        let receipt = &ctx.accounts.receipt;
+        require!(receipt.confirmed_at != 0, CustomError::DeliveryNotConfirmed);
+
+        let now = Clock::get()?.unix_timestamp;
+        let closable_at = receipt
+            .confirmed_at
+            .checked_add(ORDER_CLOSE_GRACE_PERIOD)
+            .ok_or(CustomError::Overflow)?;
+        require!(now >= closable_at, CustomError::CloseGracePeriodActive);
+
+        emit!(ReceiptClosed {
+            receipt: receipt.key(),
+            order_pubkey: receipt.order,
+            slice_index: receipt.slice_index,
+        });
+
+        Ok(())
+    }
+}
+
+fn native_token() -> Pubkey {
+    Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap()
+}
+
+fn add_order(
+    order: &mut Account<DCAOrder>,
+    user: &Signer,
+    params: &OpenOrderParams,
+    bump: u8,
+    nonce: u64,
+    now: i64,
+    platform_fee: u16,
+) {
+    order.from_token = params.from_token;
+    order.from_chain_id = params.from_chain_id;
+    order.amount_in = params.amount_in;
+    order.amount_out_min = params.amount_out_min;
+    order.amount_out_max = params.amount_out_max;
+    order.to_chain_id = params.to_chain_id;
+    order.to_token = params.to_token;
+    order.sender = user.key();
+    order.recipient = params.recipient;
+    order.execute_count = params.execute_count;
+    order.remaining_execute_count = params.execute_count;
+    order.time_interval = params.time_interval;
+    order.remaining_amount = params.amount_in;
+    order.amount_per_slice = params.amount_in / params.execute_count as u64;
+    order.platform_fee = platform_fee;
+    order.executor_tip = params.executor_tip;
+    order.referrer = params.referrer.unwrap_or_default();
+    order.referral_bps = params.referral_bps;
+    order.nonce = nonce;
+    order.time_stamp = now;
+    order.bump = bump;
+    // The first slice is executable right away, later ones follow time_interval.
+    order.last_executed_at = 0;
+    order.next_execution_at = now;
+    order.status = OrderStatus::Active;
+    order.completed_at = 0;
+    order.cancelled_at = 0;
+}
+
+/// Hands out the sender's next order nonce and lists `order` as active,
+/// creating their `UserState` on first use.
+fn register_order(
+    user_state: &mut Account<UserState>,
+    owner: Pubkey,
+    order: Pubkey,
+    bump: u8,
+) -> Result<u64> {
+    require!(
+        user_state.active_orders.len() < UserState::MAX_ACTIVE_ORDERS,
+        CustomError::TooManyActiveOrders
+    );
+    let nonce = user_state.order_nonce;
+    user_state.owner = owner;
+    user_state.bump = bump;
+    user_state.order_nonce = nonce.checked_add(1).ok_or(CustomError::Overflow)?;
+    user_state.active_orders.push(order);
+    Ok(nonce)
+}
+
+/// Drops a completed or cancelled order from the sender's active list.
+fn retire_order(user_state: &mut UserState, order: Pubkey, cancelled: bool) {
+    if let Some(index) = user_state
+        .active_orders
+        .iter()
+        .position(|key| *key == order)
+    {
+        user_state.active_orders.swap_remove(index);
+    }
+    if cancelled {
+        user_state.cancelled_orders += 1;
+    } else {
+        user_state.completed_orders += 1;
+    }
+}
+
+/// Rules shared by `open_order_*` and `update_order` for the terms a sender
+/// may change on a live order.
+fn valid_order_terms(
+    amount_out_min: &[u8; 32],
+    amount_out_max: &[u8; 32],
+    time_interval: u64,
+    recipient: &[u8; 32],
+) -> bool {
+    *amount_out_min != [0u8; 32]
+        && cmp_u256(amount_out_min, amount_out_max) != Ordering::Greater
+        && time_interval != 0
+        && i64::try_from(time_interval).is_ok()
+        && *recipient != [0u8; 32]
+}
+
+/// Fills the receipt for the slice about to run and rejects an `amount_out`
+/// quote outside the order's bounds scaled to this slice. The bounds are a
+/// price limit on the amount actually bridged or swapped, so they are scaled
+/// by the net `send_amount`, not by the gross slice. Both round up: the
+/// minimum so a small slice can't be quoted for nothing, and the maximum so
+/// the range can't end up below the minimum. Must be called before the
+/// order's counters move on.
+fn record_receipt(
+    receipt: &mut Account<ExecutionReceipt>,
+    order: &Account<DCAOrder>,
+    executor: Pubkey,
+    split: &SliceSplit,
+    amount_out: [u8; 32],
+    now: i64,
+    bump: u8,
+) -> Result<()> {
+    let amount_out_min = scale_u256(&order.amount_out_min, split.send_amount, order.amount_in)?;
+    let amount_out_max = scale_u256(&order.amount_out_max, split.send_amount, order.amount_in)?;
+    require!(
+        amount_out != [0u8; 32]
+            && cmp_u256(&amount_out, &amount_out_min) != Ordering::Less
+            && cmp_u256(&amount_out, &amount_out_max) != Ordering::Greater,
+        CustomError::AmountOutOfBounds
+    );
+
+    receipt.order = order.key();
+    receipt.slice_index = order.executed_count();
+    receipt.executor = executor;
+    receipt.execute_amount = split.execute_amount;
+    receipt.send_amount = split.send_amount;
+    receipt.to_chain_id = order.to_chain_id;
+    receipt.to_token = order.to_token;
+    receipt.recipient = order.recipient;
+    receipt.amount_out_min = amount_out_min;
+    receipt.amount_out_max = amount_out_max;
+    receipt.amount_out = amount_out;
+    receipt.executed_at = now;
+    receipt.bump = bump;
+    Ok(())
+}
+
+/// Leading fields of a Pyth `PriceUpdateV2` account, after the discriminator.
+#[derive(AnchorDeserialize)]
+struct PythPriceUpdate {
+    _write_authority: Pubkey,
+    verification_level: PythVerificationLevel,
+    feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+}
+
+#[derive(AnchorDeserialize, PartialEq)]
+enum PythVerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+/// When the route has a `PriceFeed`, requires a fully verified, fresh and
+/// tight Pyth price and checks `amount_out` is within `max_deviation_bps` of
+/// what that price gives for `send_amount`, the part of the slice left after
+/// fees and the executor tip. Routes without a feed pass through.
+fn check_oracle_quote(
+    price_feed: &UncheckedAccount,
+    price_update: Option<&UncheckedAccount>,
+    send_amount: u64,
+    amount_out: &[u8; 32],
+    now: i64,
+) -> Result<()> {
+    if price_feed.owner != &crate::ID || price_feed.data_is_empty() {
+        return Ok(());
+    }
+    let feed = PriceFeed::try_deserialize(&mut &price_feed.data.borrow()[..])?;
+
+    let price_update = price_update.ok_or(CustomError::MissingPriceUpdate)?;
+    require_keys_eq!(
+        *price_update.owner,
+        PYTH_RECEIVER_PROGRAM_ID,
+        CustomError::InvalidPriceUpdate
+    );
+    let data = price_update.data.borrow();
+    require!(
+        data.len() > 8 && data[..8] == PYTH_PRICE_UPDATE_DISCRIMINATOR,
+        CustomError::InvalidPriceUpdate
+    );
+    let update = PythPriceUpdate::deserialize(&mut &data[8..])?;
+    require!(
+        update.verification_level == PythVerificationLevel::Full
+            && update.feed_id == feed.feed_id
+            && update.price > 0,
+        CustomError::InvalidPriceUpdate
+    );
+
+    let age = now.saturating_sub(update.publish_time);
+    require!(
+        age >= 0 && (age as u64) <= feed.max_staleness,
+        CustomError::StalePrice
+    );
+
+    let price = update.price as u128;
+    require!(
+        update.conf as u128 * 10000 <= price * feed.max_confidence_bps as u128,
+        CustomError::PriceConfidenceTooWide
+    );
+
+    // send_amount * price * 10^(exponent + to_decimals - from_decimals)
+    let shift = update.exponent + feed.to_decimals as i32 - feed.from_decimals as i32;
+    let value = (send_amount as u128)
+        .checked_mul(price)
+        .ok_or(CustomError::Overflow)?;
+    let expected = if shift >= 0 {
+        10u128
+            .checked_pow(shift as u32)
+            .and_then(|scale| value.checked_mul(scale))
+            .ok_or(CustomError::Overflow)?
+    } else {
+        10u128
+            .checked_pow(shift.unsigned_abs())
+            .map_or(0, |scale| value / scale)
+    };
+
+    require!(
+        amount_out[16..] == [0u8; 16],
+        CustomError::QuoteDeviatesFromOracle
+    );
+    let quoted = u128::from_le_bytes(amount_out[..16].try_into().unwrap());
+    let deviation = quoted.abs_diff(expected);
+    require!(
+        deviation
+            .checked_mul(10000)
+            .is_some_and(|scaled| scaled <= expected.saturating_mul(feed.max_deviation_bps as u128)),
+        CustomError::QuoteDeviatesFromOracle
+    );
+
+    Ok(())
+}
+
+/// Compares two little-endian u256 values.
+fn cmp_u256(a: &[u8; 32], b: &[u8; 32]) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// `value * num / den` for a little-endian u256, rounded up.
+fn scale_u256(value: &[u8; 32], num: u64, den: u64) -> Result<[u8; 32]> {
+    require!(den > 0, CustomError::InvalidParameter);
+
+    // 4 limbs * u64 needs a fifth limb for the carry.
+    let mut limbs = [0u64; 5];
+    let mut carry = 0u128;
+    for (i, chunk) in value.chunks_exact(8).enumerate() {
+        let limb = u64::from_le_bytes(chunk.try_into().unwrap()) as u128;
+        let product = limb * num as u128 + carry;
+        limbs[i] = product as u64;
+        carry = product >> 64;
+    }
+    limbs[4] = carry as u64;
+
+    let mut remainder = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let current = (remainder << 64) | *limb as u128;
+        *limb = (current / den as u128) as u64;
+        remainder = current % den as u128;
+    }
+    if remainder > 0 {
+        for limb in limbs.iter_mut() {
+            let (sum, carried) = limb.overflowing_add(1);
+            *limb = sum;
+            if !carried {
+                break;
+            }
+        }
+    }
+    require!(limbs[4] == 0, CustomError::Overflow);
+
+    let mut scaled = [0u8; 32];
+    for (chunk, limb) in scaled.chunks_exact_mut(8).zip(limbs) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    Ok(scaled)
+}
+
+fn order_opened(order: &Account<DCAOrder>) -> OrderOpened {
+    OrderOpened {
+        order_pubkey: order.key(),
+        sender: order.sender,
+        from_token: order.from_token,
+        from_chain_id: order.from_chain_id,
+        amount_in: order.amount_in,
+        amount_out_min: order.amount_out_min,
+        amount_out_max: order.amount_out_max,
+        to_chain_id: order.to_chain_id,
+        to_token: order.to_token,
+        recipient: order.recipient,
+        execute_count: order.execute_count,
+        time_interval: order.time_interval,
+        time_stamp: order.time_stamp,
+        nonce: order.nonce,
+        amount_per_slice: order.amount_per_slice,
+        next_execution_at: order.next_execution_at,
+        platform_fee: order.platform_fee,
+        executor_tip: order.executor_tip,
+        referrer: order.referrer,
+        referral_bps: order.referral_bps,
+    }
+}
+
+fn valid_referral(params: &OpenOrderParams, config: &GlobalConfig) -> bool {
+    params.referral_bps <= config.max_referral_bps
+        && (params.referrer.is_some() || params.referral_bps == 0)
+}
+
+/// Size of the next slice: `amount_per_slice`, with the final slice taking
+/// whatever is left so rounding dust is not stranded in the order.
+fn slice_amount(order: &DCAOrder) -> Result<u64> {
+    if order.remaining_execute_count <= 1 {
+        return Ok(order.remaining_amount);
+    }
+    Ok(order.amount_per_slice.min(order.remaining_amount))
+}
+
+/// Re-derives `amount_per_slice` after a deposit or withdrawal, optionally
+/// re-spreading the remaining amount over a new number of slices.
+fn rebase_slices(order: &mut DCAOrder, remaining_execute_count: Option<u16>) -> Result<()> {
+    if let Some(count) = remaining_execute_count {
+        require!(count > 0, CustomError::InvalidParameter);
+        let executed = order.execute_count - order.remaining_execute_count;
+        order.execute_count = executed.checked_add(count).ok_or(CustomError::Overflow)?;
+        order.remaining_execute_count = count;
+    }
+    require!(
+        order.remaining_amount >= order.remaining_execute_count as u64,
+        CustomError::InsufficientFunds
+    );
+    order.amount_per_slice = order.remaining_amount / order.remaining_execute_count as u64;
+    check_slice_costs(order)
+}
+
+/// Slices must cover the platform fee and the executor tip and still send
+/// something, or the order could only ever be cancelled. Checked with the
+/// order's snapshotted fee, the most execution may charge.
+fn check_slice_costs(order: &DCAOrder) -> Result<()> {
+    split_slice(
+        order.amount_per_slice,
+        order.platform_fee,
+        order.referral_bps,
+        order.executor_tip,
+    )?;
+    Ok(())
+}
+
+/// Picks the fee from the most specific existing tier: mint and chain, then
+/// mint only, then chain only, falling back to the global `platform_fee`.
+fn resolve_platform_fee(global_fee: u16, candidates: [&UncheckedAccount; 3]) -> Result<u16> {
+    for candidate in candidates {
+        if candidate.owner == &crate::ID && !candidate.data_is_empty() {
+            let fee_tier = FeeTier::try_deserialize(&mut &candidate.data.borrow()[..])?;
+            return Ok(fee_tier.platform_fee);
+        }
+    }
+    Ok(global_fee)
+}
+
+fn schedule_fee_change(config: &mut GlobalConfig, platform_fee: u16) -> Result<()> {
+    require!(
+        platform_fee <= MAX_PLATFORM_FEE,
+        CustomError::InvalidPlatformFee
+    );
+    let eta = Clock::get()?
+        .unix_timestamp
+        .checked_add(FEE_CHANGE_DELAY)
+        .ok_or(CustomError::Overflow)?;
+    config.pending_platform_fee = platform_fee;
+    config.fee_change_eta = eta;
+
+    emit!(FeeChangeScheduled {
+        old: config.platform_fee,
+        new: platform_fee,
+        eta,
+    });
+
+    Ok(())
+}
+
+/// Reads an order written by the baseline deployment, after checking that
+/// `order` is one: the old size, and the PDA of its old seeds, which used
+/// the client-chosen `time_stamp` where orders now use a nonce.
+fn load_legacy_order(order: &UncheckedAccount) -> Result<LegacyDCAOrder> {
+    let data = order.try_borrow_data()?;
+    require!(
+        data.len() == 8 + LegacyDCAOrder::SIZE && data[..8] == *DCAOrder::DISCRIMINATOR,
+        CustomError::InvalidParameter
+    );
+    let legacy = LegacyDCAOrder::deserialize(&mut &data[8..])?;
+
+    let address = Pubkey::create_program_address(
+        &[
+            b"dca_order",
+            legacy.sender.as_ref(),
+            &legacy.time_stamp.to_le_bytes(),
+            &[legacy.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| CustomError::InvalidParameter)?;
+    require_keys_eq!(address, order.key(), CustomError::InvalidParameter);
+    Ok(legacy)
+}
+
+/// What `close = ...` does for a typed account: hand every lamport to
+/// `destination` and give the account back to the system program.
+fn close_legacy_order<'info>(
+    order: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    let balance = destination
+        .lamports()
+        .checked_add(order.lamports())
+        .ok_or(CustomError::Overflow)?;
+    **destination.try_borrow_mut_lamports()? = balance;
+    **order.try_borrow_mut_lamports()? = 0;
+    order.assign(&system_program::ID);
+    order.resize(0)?;
+    Ok(())
+}
+
+/// Closes an emptied order vault. Withheld Token-2022 transfer fees block
+/// `close_account`, so they are harvested to the mint first.
+fn close_order_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    token_mint: &InterfaceAccount<'info, Mint>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if get_mint_extension_data::<TransferFeeConfig>(&token_mint.to_account_info()).is_ok() {
+        let harvest_cpi_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            HarvestWithheldTokensToMint {
+                token_program_id: token_program.to_account_info(),
+                mint: token_mint.to_account_info(),
+            },
+        );
+        token_interface::harvest_withheld_tokens_to_mint(harvest_cpi_ctx, vec![vault.clone()])?;
+    }
+
+    let close_cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault,
+            destination,
+            authority,
+        },
+        signer,
+    );
+    token_interface::close_account(close_cpi_ctx)
+}
+
+/// Whether `account` can be credited `amount` lamports: executable accounts
+/// can't be, and an unfunded wallet has to end up rent exempt.
+fn can_receive_lamports(account: &AccountInfo, amount: u64) -> Result<bool> {
+    if account.executable {
+        return Ok(false);
+    }
+    let balance = account
+        .lamports()
+        .checked_add(amount)
+        .ok_or(CustomError::Overflow)?;
+    Ok(balance >= Rent::get()?.minimum_balance(account.data_len()))
+}
+
+/// An order's token vault, with the order PDA as its authority.
+struct OrderVault<'a, 'info> {
+    token_program: &'a Interface<'info, TokenInterface>,
+    token_mint: &'a InterfaceAccount<'info, Mint>,
+    vault: AccountInfo<'info>,
+    order: AccountInfo<'info>,
+}
+
+impl<'info> OrderVault<'_, 'info> {
+    fn pay(&self, to: AccountInfo<'info>, amount: u64, signer: &[&[&[u8]]]) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let cpi_accounts = TransferChecked {
+            from: self.vault.clone(),
+            mint: self.token_mint.to_account_info(),
+            to,
+            authority: self.order.clone(), // PDA 授权
+        };
+        let cpi_ctx =
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer);
+        token_interface::transfer_checked(cpi_ctx, amount, self.token_mint.decimals)
+    }
+
+    /// Pays the treasury, the executor tip and the referral share of a slice.
+    fn pay_slice_costs(
+        &self,
+        split: &SliceSplit,
+        treasury_token_account: AccountInfo<'info>,
+        executor_token_account: Option<AccountInfo<'info>>,
+        referrer_token_account: Option<AccountInfo<'info>>,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        // SPL Token Transfer（从 PDA 转 token 到 treasury）
+        self.pay(treasury_token_account, split.fee_amount, signer)?;
+
+        // SPL Token Transfer（从 PDA 转 token 到 executor）
+        if split.executor_tip > 0 {
+            let executor_token_account =
+                executor_token_account.ok_or(CustomError::MissingExecutorTokenAccount)?;
+            self.pay(executor_token_account, split.executor_tip, signer)?;
+        }
+
+        // SPL Token Transfer（从 PDA 转 token 到 referrer）
+        if split.referral_amount > 0 {
+            let referrer_token_account =
+                referrer_token_account.ok_or(CustomError::MissingReferrerAccount)?;
+            self.pay(referrer_token_account, split.referral_amount, signer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Refunds whatever the vault still holds before it closes. Anyone can
+    /// send tokens to the vault, and a non-zero balance would keep it open.
+    fn sweep(
+        &self,
+        refund_token_account: Option<AccountInfo<'info>>,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let leftover = token::accessor::amount(&self.vault)?;
+        if leftover > 0 {
+            let refund_token_account =
+                refund_token_account.ok_or(CustomError::MissingRefundTokenAccount)?;
+            self.pay(refund_token_account, leftover, signer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks every `execute_order_*` makes before a slice runs, returning how
+/// the slice splits.
+fn begin_slice(
+    config: &GlobalConfig,
+    executor_registry: &ExecutorRegistry,
+    executor: &Pubkey,
+    order: &DCAOrder,
+    fee_tiers: [&UncheckedAccount; 3],
+    now: i64,
+) -> Result<SliceSplit> {
+    require!(!config.paused, CustomError::ProgramPaused);
+    require!(
+        executor_registry.executors.contains(executor),
+        CustomError::UnauthorizedExecutor
+    );
+    require!(
+        order.status != OrderStatus::Paused,
+        CustomError::OrderPaused
+    );
+    require!(
+        order.status == OrderStatus::Active,
+        CustomError::OrderNotActive
+    );
+    require!(
+        now >= order.next_execution_at,
+        CustomError::ExecutionTooEarly
+    );
+
+    let execute_amount = slice_amount(order)?;
+    require!(execute_amount > 0, CustomError::NeedsMoreThanZero);
+
+    let platform_fee =
+        resolve_platform_fee(config.platform_fee, fee_tiers)?.min(order.platform_fee);
+    split_slice(
+        execute_amount,
+        platform_fee,
+        order.referral_bps,
+        order.executor_tip,
+    )
+}
+
+/// Who filled a slice and what the executor reported for it.
+struct SliceFill {
+    executor: Pubkey,
+    native_token_volume: u64,
+    amount_out: [u8; 32],
+}
+
+/// Bookkeeping after a slice has been paid out: emits `OrderExecuted`,
+/// updates the stats and moves the order on to its next slice, completing it
+/// after the last one.
+fn finish_slice(
+    order: &mut Account<DCAOrder>,
+    user_state: &mut UserState,
+    protocol_stats: &mut ProtocolStats,
+    mint_stats: &mut MintStats,
+    split: &SliceSplit,
+    fill: &SliceFill,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let is_final_slice = order.remaining_execute_count <= 1;
+
+    emit!(OrderExecuted {
+        order_pubkey: order.key(),
+        by: fill.executor,
+        native_token_volume: fill.native_token_volume,
+        amount_out: fill.amount_out,
+        execute_amount: split.execute_amount,
+        send_amount: split.send_amount,
+        executor_tip: split.executor_tip,
+        fee_amount: split.fee_amount,
+        referrer: order.referrer,
+        referral_amount: split.referral_amount,
+        remaining_amount: order.remaining_amount - split.execute_amount,
+        remaining_execute_count: order.remaining_execute_count - 1,
+        slot: clock.slot,
+        timestamp: now,
+    });
+
+    protocol_stats.slice_executed(is_final_slice);
+    mint_stats.slice_executed(split, is_final_slice);
+
+    order.remaining_amount -= split.execute_amount;
+    order.remaining_execute_count -= 1;
+    if is_final_slice {
+        retire_order(user_state, order.key(), false);
+        order.last_executed_at = now;
+        settle_order(order, OrderStatus::Completed, now);
+    } else {
+        schedule_next_execution(order, now)?;
+    }
+
+    Ok(())
+}
+
+/// Where one slice goes. `fee_amount + referral_amount` is the platform fee.
+struct SliceSplit {
+    execute_amount: u64,
+    fee_amount: u64,
+    referral_amount: u64,
+    executor_tip: u64,
+    send_amount: u64,
+}
+
+/// The platform fee (bps) is taken on the whole slice and shared with the
+/// referrer by `referral_bps`; the executor tip is a flat amount on top.
+fn split_slice(
+    execute_amount: u64,
+    platform_fee: u16,
+    referral_bps: u16,
+    executor_tip: u64,
+) -> Result<SliceSplit> {
+    let platform_amount = execute_amount
+        .checked_mul(platform_fee as u64)
+        .ok_or(CustomError::Overflow)?
+        / 10000;
+    let referral_amount = platform_amount
+        .checked_mul(referral_bps as u64)
+        .ok_or(CustomError::Overflow)?
+        / 10000;
+    let send_amount = execute_amount
+        .checked_sub(platform_amount)
+        .and_then(|amount| amount.checked_sub(executor_tip))
+        .ok_or(CustomError::InsufficientFunds)?;
+    require!(send_amount > 0, CustomError::InsufficientFunds);
+    Ok(SliceSplit {
+        execute_amount,
+        fee_amount: platform_amount - referral_amount,
+        referral_amount,
+        executor_tip,
+        send_amount,
+    })
+}
+
+/// Records the order's final state. The account stays open so clients can
+/// still show it; `close_order` reclaims the rent later.
+fn settle_order(order: &mut DCAOrder, status: OrderStatus, now: i64) {
+    order.status = status;
+    if status == OrderStatus::Completed {
+        order.completed_at = now;
+    } else {
+        order.cancelled_at = now;
+    }
+}
+
+fn schedule_next_execution(order: &mut DCAOrder, now: i64) -> Result<()> {
+    let interval = i64::try_from(order.time_interval).map_err(|_| CustomError::Overflow)?;
+    order.last_executed_at = now;
+    order.next_execution_at = now.checked_add(interval).ok_or(CustomError::Overflow)?;
+    Ok(())
+}
+
+/// Moves a lapsed `next_execution_at` forward by whole intervals, to the
+/// first scheduled slot at or after `now`.
+fn resume_schedule(order: &mut DCAOrder, now: i64) -> Result<()> {
+    if order.next_execution_at >= now {
+        return Ok(());
+    }
+    let interval = order.time_interval;
+    let lapsed = now.abs_diff(order.next_execution_at);
+    let delay = lapsed
+        .div_ceil(interval)
+        .checked_mul(interval)
+        .and_then(|delay| i64::try_from(delay).ok())
+        .ok_or(CustomError::Overflow)?;
+    order.next_execution_at = order
+        .next_execution_at
+        .checked_add(delay)
+        .ok_or(CustomError::Overflow)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        seeds = [b"global-config"],
+        bump,
+        payer = signer,
+        space = 8 + GlobalConfig::SIZE
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: may still hold an older, shorter layout that `Account` can't
+    /// deserialize; discriminator and owner are checked in the handler.
+    #[account(
+        mut,
+        seeds = [b"global-config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub global_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"global-config"],
+        bump,
+        constraint = global_config.pending_owner == pending_owner.key() @ CustomError::NotPendingOwner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub pending_owner: Signer<'info>,
+}
+
+/// Config changes gated by one of the admin roles rather than by `owner`.
+#[derive(Accounts)]
+pub struct SetConfigField<'info> {
+    #[account(
+        mut,
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddExecutor<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"executor-registry"],
+        bump,
+        payer = owner,
+        space = 8 + ExecutorRegistry::SIZE
+    )]
+    pub executor_registry: Account<'info, ExecutorRegistry>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveExecutor<'info> {
+    #[account(
+        mut,
+        seeds = [b"executor-registry"],
+        bump = executor_registry.bump
+    )]
+    pub executor_registry: Account<'info, ExecutorRegistry>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(to_chain_id: u64)]
+pub struct AddBridgeTarget<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"bridge-target".as_ref(), &to_chain_id.to_le_bytes()],
+        bump,
+        payer = owner,
+        space = 8 + BridgeTarget::SIZE
+    )]
+    pub bridge_target: Account<'info, BridgeTarget>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(to_chain_id: u64)]
+pub struct RemoveBridgeTarget<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge-target".as_ref(), &to_chain_id.to_le_bytes()],
+        bump = bridge_target.bump
+    )]
+    pub bridge_target: Account<'info, BridgeTarget>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddSwapProgram<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"swap-program-registry"],
+        bump,
+        payer = owner,
+        space = 8 + SwapProgramRegistry::SIZE
+    )]
+    pub swap_program_registry: Account<'info, SwapProgramRegistry>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveSwapProgram<'info> {
+    #[account(
+        mut,
+        seeds = [b"swap-program-registry"],
+        bump = swap_program_registry.bump
+    )]
+    pub swap_program_registry: Account<'info, SwapProgramRegistry>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(from_token: Pubkey, to_chain_id: u64)]
+pub struct SetFeeTier<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"fee-tier", from_token.as_ref(), &to_chain_id.to_le_bytes()],
+        bump,
+        payer = owner,
+        space = 8 + FeeTier::SIZE
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(from_token: Pubkey, to_chain_id: u64)]
+pub struct RemoveFeeTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee-tier", from_token.as_ref(), &to_chain_id.to_le_bytes()],
+        bump = fee_tier.bump,
+        close = owner
+    )]
+    pub fee_tier: Account<'info, FeeTier>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: PriceFeedParams)]
+pub struct SetPriceFeed<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"price-feed", params.from_token.as_ref(), &params.to_chain_id.to_le_bytes(), params.to_token.as_ref()],
+        bump,
+        payer = owner,
+        space = 8 + PriceFeed::SIZE
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(from_token: Pubkey, to_chain_id: u64, to_token: [u8; 32])]
+pub struct RemovePriceFeed<'info> {
+    #[account(
+        mut,
+        seeds = [b"price-feed", from_token.as_ref(), &to_chain_id.to_le_bytes(), to_token.as_ref()],
+        bump = price_feed.bump,
+        close = owner
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+        has_one = owner
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: OpenOrderParams)]
+pub struct OpenOrderSol<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"user-state", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = 8 + UserState::SIZE,
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"protocol-stats"],
+        bump,
+        payer = user,
+        space = 8 + ProtocolStats::SIZE,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"mint-stats", params.from_token.as_ref()],
+        bump,
+        payer = user,
+        space = 8 + MintStats::SIZE,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+
+    #[account(
+        init,
+        seeds = [b"dca_order", user.key().as_ref(), &user_state.order_nonce.to_le_bytes()],
+        bump,
+        payer = user,
+        space = 8 + DCAOrder::SIZE,
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"global-config"], bump)]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: fee tier for (from_token, to_chain_id); may not exist.
+    #[account(seeds = [b"fee-tier", params.from_token.as_ref(), &params.to_chain_id.to_le_bytes()], bump)]
+    pub mint_chain_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for from_token on any chain; may not exist.
+    #[account(seeds = [b"fee-tier", params.from_token.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub mint_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for any token to to_chain_id; may not exist.
+    #[account(seeds = [b"fee-tier", Pubkey::default().as_ref(), &params.to_chain_id.to_le_bytes()], bump)]
+    pub chain_fee_tier: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: OpenOrderParams)]
+pub struct OpenOrderSpl<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [b"user-state", user.key().as_ref()],
+        bump,
+        payer = user,
+        space = 8 + UserState::SIZE,
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"protocol-stats"],
+        bump,
+        payer = user,
+        space = 8 + ProtocolStats::SIZE,
+    )]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"mint-stats", params.from_token.as_ref()],
+        bump,
+        payer = user,
+        space = 8 + MintStats::SIZE,
+    )]
+    pub mint_stats: Box<Account<'info, MintStats>>,
+
+    #[account(
+        init,
+        seeds = [b"dca_order", user.key().as_ref(), &user_state.order_nonce.to_le_bytes()],
+        bump,
+        payer = user,
+        space = 8 + DCAOrder::SIZE,
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // The vault address is predictable from the sender's nonce, so it may
+    // already have been created by someone else.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub order_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(seeds = [b"global-config"], bump)]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: fee tier for (from_token, to_chain_id); may not exist.
+    #[account(seeds = [b"fee-tier", params.from_token.as_ref(), &params.to_chain_id.to_le_bytes()], bump)]
+    pub mint_chain_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for from_token on any chain; may not exist.
+    #[account(seeds = [b"fee-tier", params.from_token.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub mint_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for any token to to_chain_id; may not exist.
+    #[account(seeds = [b"fee-tier", Pubkey::default().as_ref(), &params.to_chain_id.to_le_bytes()], bump)]
+    pub chain_fee_tier: UncheckedAccount<'info>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrderSol<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user-state", order.sender.as_ref()],
+        bump = user_state.bump,
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(mut, seeds = [b"protocol-stats"], bump = protocol_stats.bump)]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-stats", order.from_token.as_ref()],
+        bump = mint_stats.bump,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLegacyOrderSol<'info> {
+    /// CHECK: holds the baseline layout, which `DCAOrder` can't deserialize;
+    /// checked in `load_legacy_order`.
+    #[account(mut, owner = crate::ID)]
+    pub order: UncheckedAccount<'info>,
+
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLegacyOrderSpl<'info> {
+    /// CHECK: holds the baseline layout, which `DCAOrder` can't deserialize;
+    /// checked in `load_legacy_order`.
+    #[account(mut, owner = crate::ID)]
+    pub order: UncheckedAccount<'info>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == refund_receiver.key(),
+        constraint = user_token_account.mint == token_mint.key()
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = order_token_account.owner == order.key(),
+        constraint = order_token_account.mint == token_mint.key()
+    )]
+    pub order_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Checked against the order's `from_token` in the handler.
+    #[account(mut)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"dca_order", order.sender.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+        close = refund_receiver
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrderSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"dca_order", order.sender.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == refund_receiver.key(),
+        constraint = user_token_account.mint == order.from_token
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = order_token_account.owner == order.key(),
+    )]
+    pub order_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Writable so withheld transfer fees can be harvested into it.
+    #[account(mut, address = order.from_token)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user-state", order.sender.as_ref()],
+        bump = user_state.bump,
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(mut, seeds = [b"protocol-stats"], bump = protocol_stats.bump)]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-stats", order.from_token.as_ref()],
+        bump = mint_stats.bump,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToOrderSol<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToOrderSpl<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == order.from_token
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = order_token_account.owner == order.key(),
+        constraint = order_token_account.mint == order.from_token
+    )]
+    pub order_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = order.from_token)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromOrderSol<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromOrderSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"dca_order", order.sender.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == order.sender,
+        constraint = user_token_account.mint == order.from_token
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = order_token_account.owner == order.key(),
+        constraint = order_token_account.mint == order.from_token
+    )]
+    pub order_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = order.from_token)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOrder<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteOrderSol<'info> {
+    #[account(mut)]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(mut)]
+    pub target_sol: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"bridge-target".as_ref(), &order.to_chain_id.to_le_bytes()],
+        bump = bridge_target.bump,
+    )]
+    pub bridge_target: Account<'info, BridgeTarget>,
+
+    #[account(mut, address = global_config.treasury)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub executor: Signer<'info>,
+
+    /// CHECK: receives the referral share in lamports, which any account
+    /// can; only required when the order has one.
+    #[account(mut, address = order.referrer)]
+    pub referrer: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        seeds = [b"executor-registry"],
+        bump = executor_registry.bump,
+    )]
+    pub executor_registry: Account<'info, ExecutorRegistry>,
+
+    /// CHECK: fee tier for (from_token, to_chain_id); may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub mint_chain_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for from_token on any chain; may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub mint_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: fee tier for any token to to_chain_id; may not exist.
+    #[account(seeds = [b"fee-tier", Pubkey::default().as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub chain_fee_tier: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user-state", order.sender.as_ref()],
+        bump = user_state.bump,
+    )]
+    pub user_state: Account<'info, UserState>,
+
+    #[account(mut, seeds = [b"protocol-stats"], bump = protocol_stats.bump)]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-stats", order.from_token.as_ref()],
+        bump = mint_stats.bump,
+    )]
+    pub mint_stats: Account<'info, MintStats>,
+
+    /// CHECK: price feed for the order's route; may not exist.
+    #[account(seeds = [b"price-feed", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes(), order.to_token.as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth price update; only required when `price_feed` exists, and
+    /// then checked against it in `check_oracle_quote`.
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + ExecutionReceipt::SIZE,
+        seeds = [b"execution-receipt", order.key().as_ref(), &order.executed_count().to_le_bytes()],
+        bump,
+    )]
+    pub receipt: Account<'info, ExecutionReceipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteOrderSpl<'info> {
+    #[account(
+        mut,
+        seeds = [b"dca_order", order.sender.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, DCAOrder>,
+
+    #[account(
+        mut,
+        constraint = order_token_account.owner == order.key(),
+        constraint = order_token_account.mint == order.from_token
+    )]
+    pub order_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = target_token_account.mint == order.from_token
+    )]
+    pub target_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"bridge-target".as_ref(), &order.to_chain_id.to_le_bytes()],
+        bump = bridge_target.bump,
+    )]
+    pub bridge_target: Box<Account<'info, BridgeTarget>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == global_config.treasury.key(),
+        constraint = treasury_token_account.mint == order.from_token,
+        constraint = treasury_token_account.key() == get_associated_token_address_with_program_id(&global_config.treasury.key(), &order.from_token, &token_program.key())
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = order.from_token)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"global-config"],
+        bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
 
-        require_keys_eq!(
-            ctx.accounts.executor.key(),
-            ctx.accounts.global_config.owner,
-            CustomError::OnlyOwnerCanExecute
-        );
+    #[account(
+        seeds = [b"executor-registry"],
+        bump = executor_registry.bump,
+    )]
+    pub executor_registry: Account<'info, ExecutorRegistry>,
 
-        require_keys_eq!(
-            ctx.accounts.refund_receiver.key(),
-            ctx.accounts.order.sender,
-            CustomError::InvalidRefundReceiver
-        );
+    /// CHECK: fee tier for (from_token, to_chain_id); may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub mint_chain_fee_tier: UncheckedAccount<'info>,
 
-        let amount_in = order.amount_in;
-        let platform_fee = config.platform_fee;
+    /// CHECK: fee tier for from_token on any chain; may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub mint_fee_tier: UncheckedAccount<'info>,
 
-        let fee_amount = amount_in
-            .checked_mul(platform_fee as u64)
-            .ok_or(CustomError::Overflow)?
-            / 10000;
-        let send_amount = amount_in
-            .checked_sub(fee_amount)
-            .ok_or(CustomError::Overflow)?;
-        require!(send_amount > 0, CustomError::InsufficientFunds);
+    /// CHECK: fee tier for any token to to_chain_id; may not exist.
+    #[account(seeds = [b"fee-tier", Pubkey::default().as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub chain_fee_tier: UncheckedAccount<'info>,
 
-        // PDA 签名 seeds
-        let seeds = &[
-            b"limit_order",
-            order.sender.as_ref(),
-            &order.time_stamp.to_le_bytes(),
-            &[order.bump],
-        ];
-        let signer = &[&seeds[..]];
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
 
-        // SPL Token Transfer（从 PDA 转 token 到用户）
-        let cpi_accounts = TokenTransfer {
-            from: ctx.accounts.order_token_account.to_account_info(),
-            to: ctx.accounts.target_token_account.to_account_info(),
-            authority: ctx.accounts.order.to_account_info(), // PDA 授权
-        };
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            signer,
-        );
-        token::transfer(cpi_ctx, send_amount)?;
+    /// Receives whatever else the vault holds when the final slice closes
+    /// it; only required then, and only if there is anything.
+    #[account(
+        mut,
+        constraint = refund_token_account.owner == refund_receiver.key(),
+        constraint = refund_token_account.mint == order.from_token
+    )]
+    pub refund_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-        // SPL Token Transfer（从 PDA 转 token 到用户）
-        let cpi_accounts = TokenTransfer {
-            from: ctx.accounts.order_token_account.to_account_info(),
-            to: ctx.accounts.treasury_token_account.to_account_info(),
-            authority: ctx.accounts.order.to_account_info(), // PDA 授权
-        };
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            signer,
-        );
-        token::transfer(cpi_ctx, fee_amount)?;
+    #[account(
+        mut,
+        seeds = [b"user-state", order.sender.as_ref()],
+        bump = user_state.bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
 
-        let close_cpi_accounts = CloseAccount {
-            account: ctx.accounts.order_token_account.to_account_info(),
-            destination: ctx.accounts.refund_receiver.to_account_info(),
-            authority: ctx.accounts.order.to_account_info(),
-        };
-        let close_cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            close_cpi_accounts,
-            signer,
-        );
-        token::close_account(close_cpi_ctx)?;
+    #[account(mut, seeds = [b"protocol-stats"], bump = protocol_stats.bump)]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
 
-        emit!(OrderExecuted {
-            order_pubkey: ctx.accounts.order.key(),
-            by: ctx.accounts.executor.key(),
-            native_token_volume,
-        });
+    #[account(
+        mut,
+        seeds = [b"mint-stats", order.from_token.as_ref()],
+        bump = mint_stats.bump,
+    )]
+    pub mint_stats: Box<Account<'info, MintStats>>,
 
-        if ctx.accounts.order.remaining_amount == execute_amount
-            || ctx.accounts.order.remaining_amount == 1
-        {
-            ctx.accounts
-                .order
-                .close(ctx.accounts.refund_receiver.to_account_info())?;
-        } else {
-            ctx.accounts.order.remaining_amount -= execute_amount;
-            ctx.accounts.order.remaining_execute_count -= 1;
-        }
+    #[account(mut)]
+    pub executor: Signer<'info>,
 
-        Ok(())
-    }
-}
+    /// Receives `order.executor_tip`; only required when the tip is non-zero.
+    #[account(
+        mut,
+        constraint = executor_token_account.owner == executor.key(),
+        constraint = executor_token_account.mint == order.from_token
+    )]
+    pub executor_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-fn native_token() -> Pubkey {
-    Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap()
-}
+    /// Receives the referral share; only required when the order has one.
+    #[account(
+        mut,
+        constraint = referrer_token_account.owner == order.referrer,
+        constraint = referrer_token_account.mint == order.from_token
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-fn add_order(order: &mut Account<DCAOrder>, user: &Signer, params: &OpenOrderParams, bump: u8) {
-    order.from_token = params.from_token;
-    order.from_chain_id = params.from_chain_id;
-    order.amount_in = params.amount_in;
-    order.amount_out_min = params.amount_out_min;
-    order.amount_out_max = params.amount_out_max;
-    order.to_chain_id = params.to_chain_id;
-    order.to_token = params.to_token;
-    order.sender = user.key();
-    order.recipient = params.recipient;
-    order.execute_count = params.execute_count;
-    order.remaining_execute_count = params.execute_count;
-    order.time_interval = params.time_interval;
-    order.remaining_amount = params.amount_in;
-    order.time_stamp = params.time_stamp;
-    order.bump = bump;
-}
+    /// CHECK: price feed for the order's route; may not exist.
+    #[account(seeds = [b"price-feed", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes(), order.to_token.as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth price update; only required when `price_feed` exists, and
+    /// then checked against it in `check_oracle_quote`.
+    pub price_update: Option<UncheckedAccount<'info>>,
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
     #[account(
         init,
-        seeds = [b"global-config"],
+        payer = executor,
+        space = 8 + ExecutionReceipt::SIZE,
+        seeds = [b"execution-receipt", order.key().as_ref(), &order.executed_count().to_le_bytes()],
         bump,
-        payer = signer,
-        space = 8 + GlobalConfig::SIZE
     )]
-    pub global_config: Account<'info, GlobalConfig>,
-
-    #[account(mut)]
-    pub signer: Signer<'info>,
+    pub receipt: Box<Account<'info, ExecutionReceipt>>,
 
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+pub struct ExecuteOrderSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"dca_order", order.sender.as_ref(), &order.nonce.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Box<Account<'info, DCAOrder>>,
+
+    /// For native SOL orders, the WSOL vault for this slice; created here if
+    /// needed, with the executor putting up its rent until the slice closes it.
+    #[account(
+        init_if_needed,
+        payer = executor,
+        associated_token::mint = token_mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub order_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The order's `recipient`, holding `to_token`.
+    #[account(
+        mut,
+        constraint = recipient_token_account.owner == Pubkey::new_from_array(order.recipient),
+        constraint = recipient_token_account.mint == Pubkey::new_from_array(order.to_token)
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(
         mut,
+        constraint = treasury_token_account.owner == global_config.treasury.key(),
+        constraint = treasury_token_account.mint == order.from_token,
+        constraint = treasury_token_account.key() == get_associated_token_address_with_program_id(&global_config.treasury.key(), &order.from_token, &token_program.key())
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = order.from_token)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
         seeds = [b"global-config"],
         bump,
-        has_one = owner
     )]
-    pub global_config: Account<'info, GlobalConfig>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 
-    pub owner: Signer<'info>,
-}
+    #[account(
+        seeds = [b"executor-registry"],
+        bump = executor_registry.bump,
+    )]
+    pub executor_registry: Box<Account<'info, ExecutorRegistry>>,
 
-#[derive(Accounts)]
-#[instruction(params: OpenOrderParams)]
-pub struct OpenOrderSol<'info> {
     #[account(
-        init,
-        seeds = [b"dca_order", user.key().as_ref(),&params.time_stamp.to_le_bytes()],
-        bump,
-        payer = user,
-        space = 8 + DCAOrder::SIZE,
+        seeds = [b"swap-program-registry"],
+        bump = swap_program_registry.bump,
     )]
-    pub order: Account<'info, DCAOrder>,
+    pub swap_program_registry: Box<Account<'info, SwapProgramRegistry>>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    /// CHECK: must be listed in `swap_program_registry`.
+    #[account(executable)]
+    pub swap_program: UncheckedAccount<'info>,
 
-    #[account(seeds = [b"global-config"], bump)]
-    pub global_config: Account<'info, GlobalConfig>,
+    /// CHECK: fee tier for (from_token, to_chain_id); may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub mint_chain_fee_tier: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
-}
+    /// CHECK: fee tier for from_token on any chain; may not exist.
+    #[account(seeds = [b"fee-tier", order.from_token.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub mint_fee_tier: UncheckedAccount<'info>,
 
-#[derive(Accounts)]
-#[instruction(params: OpenOrderParams)]
-pub struct OpenOrderSpl<'info> {
+    /// CHECK: fee tier for any token to to_chain_id; may not exist.
+    #[account(seeds = [b"fee-tier", Pubkey::default().as_ref(), &order.to_chain_id.to_le_bytes()], bump)]
+    pub chain_fee_tier: UncheckedAccount<'info>,
+
+    /// CHECK: price feed for the order's route; may not exist.
+    #[account(seeds = [b"price-feed", order.from_token.as_ref(), &order.to_chain_id.to_le_bytes(), order.to_token.as_ref()], bump)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    /// CHECK: Pyth price update; only required when `price_feed` exists, and
+    /// then checked against it in `check_oracle_quote`.
+    pub price_update: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub refund_receiver: SystemAccount<'info>,
+
+    /// Receives whatever else the vault holds when the final slice closes
+    /// it; only required then, and only if there is anything.
     #[account(
-        init,
-        seeds = [b"dca_order", user.key().as_ref(),&params.time_stamp.to_le_bytes()],
-        bump,
-        payer = user,
-        space = 8 + DCAOrder::SIZE,
+        mut,
+        constraint = refund_token_account.owner == refund_receiver.key(),
+        constraint = refund_token_account.mint == order.from_token
     )]
-    pub order: Account<'info, DCAOrder>,
+    pub refund_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"user-state", order.sender.as_ref()],
+        bump = user_state.bump,
+    )]
+    pub user_state: Box<Account<'info, UserState>>,
+
+    #[account(mut, seeds = [b"protocol-stats"], bump = protocol_stats.bump)]
+    pub protocol_stats: Box<Account<'info, ProtocolStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-stats", order.from_token.as_ref()],
+        bump = mint_stats.bump,
+    )]
+    pub mint_stats: Box<Account<'info, MintStats>>,
 
     #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub executor: Signer<'info>,
 
+    /// Receives `order.executor_tip`; only required when the tip is non-zero.
     #[account(
-        init,
-        payer = user,
-        associated_token::mint = token_mint,
-        associated_token::authority = order
+        mut,
+        constraint = executor_token_account.owner == executor.key(),
+        constraint = executor_token_account.mint == order.from_token
     )]
-    pub order_token_account: Account<'info, TokenAccount>,
+    pub executor_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(seeds = [b"global-config"], bump)]
-    pub global_config: Account<'info, GlobalConfig>,
-    pub token_mint: Account<'info, Mint>,
+    /// Receives the referral share; only required when the order has one.
+    #[account(
+        mut,
+        constraint = referrer_token_account.owner == order.referrer,
+        constraint = referrer_token_account.mint == order.from_token
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(
+        init,
+        payer = executor,
+        space = 8 + ExecutionReceipt::SIZE,
+        seeds = [b"execution-receipt", order.key().as_ref(), &order.executed_count().to_le_bytes()],
+        bump,
+    )]
+    pub receipt: Box<Account<'info, ExecutionReceipt>>,
 
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-
     pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
 }
 
 #[derive(Accounts)]
-pub struct CancelOrderSol<'info> {
+pub struct ConfirmDelivery<'info> {
     #[account(
         mut,
-        close = refund_receiver
+        seeds = [b"execution-receipt", receipt.order.as_ref(), &receipt.slice_index.to_le_bytes()],
+        bump = receipt.bump,
     )]
-    pub order: Account<'info, DCAOrder>,
-
-    #[account(mut)]
-    pub user: Signer<'info>,
+    pub receipt: Account<'info, ExecutionReceipt>,
 
-    #[account(mut)]
-    pub refund_receiver: SystemAccount<'info>,
+    pub attester: Signer<'info>,
 
     #[account(
         seeds = [b"global-config"],
@@ -579,116 +3616,278 @@ pub struct CancelOrderSol<'info> {
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
-    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"executor-registry"],
+        bump = executor_registry.bump,
+    )]
+    pub executor_registry: Account<'info, ExecutorRegistry>,
 }
 
 #[derive(Accounts)]
-pub struct CancelOrderSpl<'info> {
+pub struct CloseReceipt<'info> {
     #[account(
         mut,
-        seeds = [b"dca_order", order.sender.as_ref(), &order.time_stamp.to_le_bytes()],
-        bump = order.bump,
-        close = refund_receiver
+        seeds = [b"execution-receipt", receipt.order.as_ref(), &receipt.slice_index.to_le_bytes()],
+        bump = receipt.bump,
+        close = executor
     )]
-    pub order: Account<'info, DCAOrder>,
+    pub receipt: Account<'info, ExecutionReceipt>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(mut, address = receipt.executor @ CustomError::UnauthorizedExecutor)]
+    pub executor: Signer<'info>,
+}
 
-    #[account(
-        mut,
-        constraint = user_token_account.owner == refund_receiver.key(),
-        constraint = user_token_account.mint == order.from_token
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
+#[account]
+pub struct GlobalConfig {
+    pub owner: Pubkey,
+    pub platform_fee: u16,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub pending_owner: Pubkey,
+    pub pauser: Pubkey,
+    pub fee_manager: Pubkey,
+    pub treasury_manager: Pubkey,
+    pub frozen: bool,
+    pub pending_platform_fee: u16,
+    pub fee_change_eta: i64,
+    pub max_referral_bps: u16,
+    pub reserved: [u8; 64], // Reserved space for future use
+}
 
-    #[account(
-        mut,
-        constraint = order_token_account.owner == order.key(),
-    )]
-    pub order_token_account: Account<'info, TokenAccount>,
+impl GlobalConfig {
+    pub const SIZE: usize = 32 + 2 + 32 + 1 + 32 + 32 + 32 + 32 + 1 + 2 + 8 + 2 + 64;
 
-    #[account(mut)]
-    pub refund_receiver: SystemAccount<'info>,
+    // The owner keeps every role so it can always step in.
+    pub fn is_pauser(&self, key: &Pubkey) -> bool {
+        *key == self.pauser || *key == self.owner
+    }
+
+    pub fn is_fee_manager(&self, key: &Pubkey) -> bool {
+        *key == self.fee_manager || *key == self.owner
+    }
+
+    pub fn is_treasury_manager(&self, key: &Pubkey) -> bool {
+        *key == self.treasury_manager || *key == self.owner
+    }
+}
+
+#[account]
+pub struct ExecutorRegistry {
+    pub executors: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl ExecutorRegistry {
+    pub const MAX_EXECUTORS: usize = 16;
+    pub const SIZE: usize = 4 + 32 * Self::MAX_EXECUTORS + 1;
+}
+
+/// Approved bridge / solver vaults for one destination chain.
+#[account]
+pub struct BridgeTarget {
+    pub to_chain_id: u64,
+    pub targets: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl BridgeTarget {
+    pub const MAX_TARGETS: usize = 8;
+    pub const SIZE: usize = 8 + 4 + 32 * Self::MAX_TARGETS + 1;
+}
+
+#[account]
+pub struct SwapProgramRegistry {
+    pub programs: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl SwapProgramRegistry {
+    pub const MAX_SWAP_PROGRAMS: usize = 8;
+    pub const SIZE: usize = 4 + 32 * Self::MAX_SWAP_PROGRAMS + 1;
+}
+
+#[account]
+pub struct FeeTier {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub platform_fee: u16,
+    pub bump: u8,
+}
 
-    #[account(
-        seeds = [b"global-config"],
-        bump
-    )]
-    pub global_config: Account<'info, GlobalConfig>,
+impl FeeTier {
+    pub const SIZE: usize = 32 + 8 + 2 + 1;
+}
 
-    pub token_program: Program<'info, Token>,
+/// Oracle settings for one (from_token, to_chain_id, to_token) route.
+#[account]
+pub struct PriceFeed {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    // Pyth feed pricing from_token in to_token.
+    pub feed_id: [u8; 32],
+    pub from_decimals: u8,
+    pub to_decimals: u8,
+    // Seconds.
+    pub max_staleness: u64,
+    pub max_confidence_bps: u16,
+    pub max_deviation_bps: u16,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteOrderSol<'info> {
-    #[account(mut)]
-    pub order: Account<'info, DCAOrder>,
+impl PriceFeed {
+    pub const SIZE: usize = 32 + 8 + 32 + 32 + 1 + 1 + 8 + 2 + 2 + 1;
+}
 
-    #[account(mut)]
-    pub target_sol: SystemAccount<'info>,
+/// One per executed slice. The execution half is written by `execute_order_*`,
+/// the delivery half by `confirm_delivery`.
+#[account]
+pub struct ExecutionReceipt {
+    pub order: Pubkey,
+    pub slice_index: u16,
+    pub executor: Pubkey,
+    pub execute_amount: u64,
+    pub send_amount: u64,
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    pub recipient: [u8; 32],
+    // Order bounds scaled to this slice (little-endian u256).
+    pub amount_out_min: [u8; 32],
+    pub amount_out_max: [u8; 32],
+    // Executor's quote for this slice, within the bounds above.
+    pub amount_out: [u8; 32],
+    pub executed_at: i64,
+    pub dest_tx_hash: [u8; 32],
+    pub delivered_amount: [u8; 32],
+    pub confirmed_by: Pubkey,
+    // 0 until confirm_delivery.
+    pub confirmed_at: i64,
+    pub bump: u8,
+}
 
-    #[account(mut, address = global_config.treasury)]
-    pub treasury: SystemAccount<'info>,
+impl ExecutionReceipt {
+    pub const SIZE: usize =
+        32 + 2 + 32 + 8 + 8 + 8 + 32 + 32 + 32 + 32 + 32 + 8 + 32 + 32 + 32 + 8 + 1;
+}
 
-    pub executor: Signer<'info>,
+/// Per-sender order nonce and index, so wallets can list a sender's orders
+/// with a single account fetch.
+#[account]
+pub struct UserState {
+    pub owner: Pubkey,
+    // Seed of the sender's next order; also the number of orders opened.
+    pub order_nonce: u64,
+    pub bump: u8,
+    pub completed_orders: u64,
+    pub cancelled_orders: u64,
+    pub active_orders: Vec<Pubkey>,
+}
 
-    #[account(
-        seeds = [b"global-config"],
-        bump,
-    )]
-    pub global_config: Account<'info, GlobalConfig>,
+impl UserState {
+    pub const MAX_ACTIVE_ORDERS: usize = 64;
+    pub const SIZE: usize = 32 + 8 + 1 + 8 + 8 + 4 + 32 * Self::MAX_ACTIVE_ORDERS;
+}
 
-    pub refund_receiver: SystemAccount<'info>,
+/// Protocol-wide counters. Fixed-size fields only, so dashboards can decode
+/// the account without an indexer.
+#[account]
+pub struct ProtocolStats {
+    pub orders_opened: u64,
+    pub active_orders: u64,
+    pub orders_completed: u64,
+    pub orders_cancelled: u64,
+    pub slices_executed: u64,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct ExecuteOrderSpl<'info> {
-    #[account(mut)]
-    pub order: Account<'info, DCAOrder>,
+impl ProtocolStats {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8 + 1;
 
-    #[account(
-        mut,
-        constraint = order_token_account.owner == order.key()
-    )]
-    pub order_token_account: Account<'info, TokenAccount>,
+    fn order_opened(&mut self, bump: u8) {
+        self.bump = bump;
+        self.orders_opened += 1;
+        self.active_orders += 1;
+    }
 
-    #[account(mut)]
-    pub target_token_account: Account<'info, TokenAccount>,
+    fn slice_executed(&mut self, is_final_slice: bool) {
+        self.slices_executed += 1;
+        if is_final_slice {
+            self.active_orders = self.active_orders.saturating_sub(1);
+            self.orders_completed += 1;
+        }
+    }
 
-    #[account(
-        mut,
-        constraint = treasury_token_account.owner == global_config.treasury.key(),
-        constraint = treasury_token_account.mint == order.from_token,
-        constraint = treasury_token_account.key() == get_associated_token_address(&global_config.treasury.key(), &order.from_token)
-    )]
-    pub treasury_token_account: Account<'info, TokenAccount>,
+    fn order_cancelled(&mut self) {
+        self.active_orders = self.active_orders.saturating_sub(1);
+        self.orders_cancelled += 1;
+    }
+}
 
-    #[account(
-        seeds = [b"global-config"],
-        bump,
-    )]
-    pub global_config: Account<'info, GlobalConfig>,
+/// Per-`from_token` counters; amounts are in the mint's base units.
+#[account]
+pub struct MintStats {
+    pub mint: Pubkey,
+    pub orders_opened: u64,
+    pub active_orders: u64,
+    // Executed slices, fees and tips included.
+    pub volume: u128,
+    // Treasury and referral shares.
+    pub fees_collected: u128,
+    pub executor_tips: u128,
+    pub refunded: u128,
+    pub bump: u8,
+}
 
-    #[account(mut)]
-    pub refund_receiver: SystemAccount<'info>,
+impl MintStats {
+    pub const SIZE: usize = 32 + 8 + 8 + 16 + 16 + 16 + 16 + 1;
 
-    pub executor: Signer<'info>,
+    fn order_opened(&mut self, mint: Pubkey, bump: u8) {
+        self.mint = mint;
+        self.bump = bump;
+        self.orders_opened += 1;
+        self.active_orders += 1;
+    }
+
+    fn slice_executed(&mut self, split: &SliceSplit, is_final_slice: bool) {
+        self.volume += split.execute_amount as u128;
+        self.fees_collected += split.fee_amount as u128 + split.referral_amount as u128;
+        self.executor_tips += split.executor_tip as u128;
+        if is_final_slice {
+            self.active_orders = self.active_orders.saturating_sub(1);
+        }
+    }
 
-    pub token_program: Program<'info, Token>,
+    fn order_cancelled(&mut self, refunded: u64) {
+        self.active_orders = self.active_orders.saturating_sub(1);
+        self.refunded += refunded as u128;
+    }
 }
 
-#[account]
-pub struct GlobalConfig {
-    pub owner: Pubkey,
-    pub platform_fee: u16,
-    pub treasury: Pubkey,
-    pub paused: bool,
-    pub reserved: [u8; 128], // Reserved space for future use
+/// `DCAOrder` as the baseline deployment wrote it, at the PDA of
+/// `[b"dca_order", sender, time_stamp]`. Only read, by the legacy cancels.
+#[derive(AnchorDeserialize)]
+pub struct LegacyDCAOrder {
+    pub from_token: Pubkey,
+    pub from_chain_id: u64,
+    pub amount_in: u64,
+    pub amount_out_min: [u8; 32],
+    pub amount_out_max: [u8; 32],
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    pub sender: Pubkey,
+    pub recipient: [u8; 32],
+    pub execute_count: u16,
+    pub remaining_execute_count: u16,
+    pub time_interval: u64,
+    pub remaining_amount: u64,
+    pub time_stamp: i64,
+    pub bump: u8,
 }
 
-impl GlobalConfig {
-    pub const SIZE: usize = 32 + 2 + 32 + 1 + 128;
+impl LegacyDCAOrder {
+    // Counts the discriminator and was allocated as `8 + SIZE`, like
+    // `DCAOrder::SIZE`.
+    pub const SIZE: usize = 8 + 32 + 8 + 8 + 32 + 32 + 8 + 32 + 32 + 32 + 2 + 2 + 8 + 8 + 8 + 1;
 }
 
 #[account]
@@ -696,6 +3895,8 @@ pub struct DCAOrder {
     pub from_token: Pubkey,
     pub from_chain_id: u64,
     pub amount_in: u64,
+    // Output limits for `amount_in`; each slice is held to them pro rata on
+    // its net `send_amount`, so fees and tips don't erode the price limit.
     pub amount_out_min: [u8; 32],
     pub amount_out_max: [u8; 32],
     pub to_chain_id: u64,
@@ -706,8 +3907,26 @@ pub struct DCAOrder {
     pub remaining_execute_count: u16,
     pub time_interval: u64,
     pub remaining_amount: u64,
+    // Clock time at open.
     pub time_stamp: i64,
     pub bump: u8,
+    pub last_executed_at: i64,
+    pub next_execution_at: i64,
+    pub amount_per_slice: u64,
+    // Fee resolved at open; execution may charge less but never more.
+    pub platform_fee: u16,
+    // Flat amount paid to the executor out of each slice.
+    pub executor_tip: u64,
+    // `Pubkey::default()` when the order has no referrer.
+    pub referrer: Pubkey,
+    // Referrer's share of the platform fee, in bps.
+    pub referral_bps: u16,
+    // PDA seed, taken from the sender's `UserState` at open.
+    pub nonce: u64,
+    pub status: OrderStatus,
+    pub completed_at: i64,
+    // Also set when the order is settled as `Expired`.
+    pub cancelled_at: i64,
 }
 
 impl DCAOrder {
@@ -726,7 +3945,65 @@ impl DCAOrder {
         + 8   // time_interval
         + 8   // remaining_amount
         + 8  //time_stamp
-        + 1; // bump
+        + 1  // bump
+        + 8  // last_executed_at
+        + 8  // next_execution_at
+        + 8  // amount_per_slice
+        + 2  // platform_fee
+        + 8  // executor_tip
+        + 32 // referrer
+        + 2  // referral_bps
+        + 8  // nonce
+        + 1  // status
+        + 8  // completed_at
+        + 8; // cancelled_at
+
+    /// Slices already executed, i.e. the index of the next slice.
+    pub fn executed_count(&self) -> u16 {
+        self.execute_count - self.remaining_execute_count
+    }
+
+    /// Still holds escrow, i.e. neither completed nor cancelled/expired.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::Active | OrderStatus::Paused)
+    }
+
+    /// Active, but executors have left its pending slice unfilled for longer
+    /// than `ORDER_EXPIRY_PERIOD`.
+    pub fn is_overdue(&self, now: i64) -> bool {
+        self.status == OrderStatus::Active
+            && now > self.next_execution_at.saturating_add(ORDER_EXPIRY_PERIOD)
+    }
+
+    pub fn settled_at(&self) -> i64 {
+        if self.status == OrderStatus::Completed {
+            self.completed_at
+        } else {
+            self.cancelled_at
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrderStatus {
+    Active,
+    Paused,
+    Completed,
+    Cancelled,
+    Expired,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceFeedParams {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    pub feed_id: [u8; 32],
+    pub from_decimals: u8,
+    pub to_decimals: u8,
+    pub max_staleness: u64,
+    pub max_confidence_bps: u16,
+    pub max_deviation_bps: u16,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -741,7 +4018,17 @@ pub struct OpenOrderParams {
     pub recipient: [u8; 32],
     pub execute_count: u16,
     pub time_interval: u64,
-    pub time_stamp: i64,
+    pub executor_tip: u64,
+    pub referrer: Option<Pubkey>,
+    pub referral_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateOrderParams {
+    pub amount_out_min: [u8; 32],
+    pub amount_out_max: [u8; 32],
+    pub time_interval: u64,
+    pub recipient: [u8; 32],
 }
 
 #[event]
@@ -752,6 +4039,12 @@ pub struct Initialized {
     pub paused: bool,
 }
 
+#[event]
+pub struct ConfigMigrated {
+    pub old_len: u64,
+    pub new_len: u64,
+}
+
 #[event]
 pub struct ConfigUpdated {
     pub owner: Pubkey,
@@ -760,28 +4053,238 @@ pub struct ConfigUpdated {
     pub paused: bool,
 }
 
+#[event]
+pub struct OwnerProposed {
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+#[event]
+pub struct OwnerAccepted {
+    pub previous_owner: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub pauser: Pubkey,
+    pub fee_manager: Pubkey,
+    pub treasury_manager: Pubkey,
+}
+
+#[event]
+pub struct PausedChanged {
+    pub old: bool,
+    pub new: bool,
+}
+
+#[event]
+pub struct FrozenChanged {
+    pub old: bool,
+    pub new: bool,
+}
+
+#[event]
+pub struct FeeChanged {
+    pub old: u16,
+    pub new: u16,
+}
+
+#[event]
+pub struct FeeChangeScheduled {
+    pub old: u16,
+    pub new: u16,
+    pub eta: i64,
+}
+
+#[event]
+pub struct MaxReferralBpsChanged {
+    pub old: u16,
+    pub new: u16,
+}
+
+#[event]
+pub struct TreasuryChanged {
+    pub old: Pubkey,
+    pub new: Pubkey,
+}
+
+#[event]
+pub struct FeeTierSet {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub platform_fee: u16,
+}
+
+#[event]
+pub struct FeeTierRemoved {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+}
+
+#[event]
+pub struct BridgeTargetAdded {
+    pub to_chain_id: u64,
+    pub target: Pubkey,
+}
+
+#[event]
+pub struct BridgeTargetRemoved {
+    pub to_chain_id: u64,
+    pub target: Pubkey,
+}
+
+#[event]
+pub struct SwapProgramAdded {
+    pub program: Pubkey,
+}
+
+#[event]
+pub struct SwapProgramRemoved {
+    pub program: Pubkey,
+}
+
+#[event]
+pub struct PriceFeedSet {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    pub feed_id: [u8; 32],
+}
+
+#[event]
+pub struct PriceFeedRemoved {
+    pub from_token: Pubkey,
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+}
+
+#[event]
+pub struct ExecutorAdded {
+    pub executor: Pubkey,
+}
+
+#[event]
+pub struct ExecutorRemoved {
+    pub executor: Pubkey,
+}
+
+// Carries the full order so indexers don't need to fetch the account.
 #[event]
 pub struct OrderOpened {
     pub order_pubkey: Pubkey,
+    pub sender: Pubkey,
+    pub from_token: Pubkey,
+    pub from_chain_id: u64,
+    pub amount_in: u64,
+    pub amount_out_min: [u8; 32],
+    pub amount_out_max: [u8; 32],
+    pub to_chain_id: u64,
+    pub to_token: [u8; 32],
+    pub recipient: [u8; 32],
+    pub execute_count: u16,
+    pub time_interval: u64,
+    pub time_stamp: i64,
+    pub nonce: u64,
+    pub amount_per_slice: u64,
+    pub next_execution_at: i64,
+    pub platform_fee: u16,
+    pub executor_tip: u64,
+    pub referrer: Pubkey,
+    pub referral_bps: u16,
 }
 
 #[event]
 pub struct OrderCancelled {
     pub order_pubkey: Pubkey,
     pub by: Pubkey,
+    pub refunded_amount: u64,
+    // `Cancelled`, or `Expired` if the order was overdue.
+    pub status: OrderStatus,
+}
+#[event]
+pub struct OrderClosed {
+    pub order_pubkey: Pubkey,
+    pub status: OrderStatus,
+}
+#[event]
+pub struct OrderDeposited {
+    pub order_pubkey: Pubkey,
+    pub amount: u64,
+    pub remaining_amount: u64,
+    pub remaining_execute_count: u16,
+}
+
+#[event]
+pub struct OrderWithdrawn {
+    pub order_pubkey: Pubkey,
+    pub amount: u64,
+    pub remaining_amount: u64,
+    pub remaining_execute_count: u16,
+}
+
+#[event]
+pub struct OrderPaused {
+    pub order_pubkey: Pubkey,
+}
+
+#[event]
+pub struct OrderResumed {
+    pub order_pubkey: Pubkey,
+    pub next_execution_at: i64,
+}
+
+#[event]
+pub struct OrderUpdated {
+    pub order_pubkey: Pubkey,
+    pub amount_out_min: [u8; 32],
+    pub amount_out_max: [u8; 32],
+    pub time_interval: u64,
+    pub recipient: [u8; 32],
 }
+
 #[event]
 pub struct OrderExecuted {
     pub order_pubkey: Pubkey,
     pub by: Pubkey,
     pub native_token_volume: u64,
+    pub amount_out: [u8; 32],
+    // Slice taken from the order; `send_amount` is what reached the target.
+    pub execute_amount: u64,
+    pub send_amount: u64,
+    pub executor_tip: u64,
+    pub fee_amount: u64,
+    pub referrer: Pubkey,
+    pub referral_amount: u64,
+    // Order state after this slice; zero count means it is now `Completed`.
+    pub remaining_amount: u64,
+    pub remaining_execute_count: u16,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeliveryConfirmed {
+    pub receipt: Pubkey,
+    pub order_pubkey: Pubkey,
+    pub slice_index: u16,
+    pub dest_tx_hash: [u8; 32],
+    pub delivered_amount: [u8; 32],
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct ReceiptClosed {
+    pub receipt: Pubkey,
+    pub order_pubkey: Pubkey,
+    pub slice_index: u16,
 }
 
 #[error_code]
 pub enum CustomError {
     #[msg("The program is currently paused.")]
     ProgramPaused,
-    #[msg("Platform fee must be between 0 and 10000.")]
+    #[msg("Platform fee exceeds MAX_PLATFORM_FEE.")]
     InvalidPlatformFee,
     #[msg("Invalid parameter")]
     InvalidParameter,
@@ -799,4 +4302,84 @@ pub enum CustomError {
     OnlyOwnerCanExecute,
     #[msg("Need more than zero.")]
     NeedsMoreThanZero,
+    #[msg("Order is not due for execution yet.")]
+    ExecutionTooEarly,
+    #[msg("Executor is not registered.")]
+    UnauthorizedExecutor,
+    #[msg("Executor is already registered.")]
+    ExecutorAlreadyExists,
+    #[msg("Executor not found.")]
+    ExecutorNotFound,
+    #[msg("Executor registry is full.")]
+    ExecutorRegistryFull,
+    #[msg("Only the order sender can do this.")]
+    OnlySender,
+    #[msg("Signer is not the pending owner.")]
+    NotPendingOwner,
+    #[msg("Signer does not hold the required role.")]
+    Unauthorized,
+    #[msg("The program is frozen.")]
+    ProgramFrozen,
+    #[msg("No platform fee change is pending.")]
+    NoPendingFeeChange,
+    #[msg("Platform fee change is still timelocked.")]
+    FeeChangeTimelocked,
+    #[msg("Executor token account is required to pay the executor tip.")]
+    MissingExecutorTokenAccount,
+    #[msg("Referral bps exceed the maximum or have no referrer.")]
+    InvalidReferral,
+    #[msg("Referrer account is required to pay the referral share.")]
+    MissingReferrerAccount,
+    #[msg("Delivery for this slice has already been confirmed.")]
+    DeliveryAlreadyConfirmed,
+    #[msg("Quoted amount out is outside the order's bounds for this slice.")]
+    AmountOutOfBounds,
+    #[msg("A price update is required for this route.")]
+    MissingPriceUpdate,
+    #[msg("Price update is not a verified update for this route's feed.")]
+    InvalidPriceUpdate,
+    #[msg("Oracle price is too old.")]
+    StalePrice,
+    #[msg("Oracle price confidence interval is too wide.")]
+    PriceConfidenceTooWide,
+    #[msg("Quoted amount out deviates too far from the oracle price.")]
+    QuoteDeviatesFromOracle,
+    #[msg("Swap program is already registered.")]
+    SwapProgramAlreadyExists,
+    #[msg("Swap program not found.")]
+    SwapProgramNotFound,
+    #[msg("Swap program registry is full.")]
+    SwapProgramRegistryFull,
+    #[msg("Swap program is not registered.")]
+    SwapProgramNotAllowed,
+    #[msg("Swap did not spend exactly the slice or tampered with the vault.")]
+    InvalidSwapResult,
+    #[msg("Bridge target is already registered.")]
+    BridgeTargetAlreadyExists,
+    #[msg("Bridge target not found.")]
+    BridgeTargetNotFound,
+    #[msg("Bridge target registry is full.")]
+    BridgeTargetRegistryFull,
+    #[msg("Target is not an approved bridge target for this chain.")]
+    UnapprovedTarget,
+    #[msg("Sender has too many active orders.")]
+    TooManyActiveOrders,
+    #[msg("Order is not active.")]
+    OrderNotActive,
+    #[msg("Order has not completed or been cancelled.")]
+    OrderNotSettled,
+    #[msg("Account can only be closed after the grace period.")]
+    CloseGracePeriodActive,
+    #[msg("Order is paused by its sender.")]
+    OrderPaused,
+    #[msg("Order is not paused.")]
+    OrderNotPaused,
+    #[msg("The slice's executor cannot confirm its own delivery.")]
+    SelfAttestedDelivery,
+    #[msg("Delivery has not been confirmed.")]
+    DeliveryNotConfirmed,
+    #[msg("Global config already has the current layout.")]
+    ConfigAlreadyMigrated,
+    #[msg("Refund token account is required to empty the order vault.")]
+    MissingRefundTokenAccount,
 }
@@ -0,0 +1,31 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import * as dotenv from "dotenv";
+dotenv.config();
+
+// 升级程序后执行一次，把旧布局的 global-config 扩容到当前大小
+async function main() {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+
+  const owner = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  console.log(globalConfigPda);
+
+  await program.methods
+    .migrateConfig()
+    .accounts({
+      globalConfig: globalConfigPda,
+      owner,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+main();
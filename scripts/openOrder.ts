@@ -10,6 +10,11 @@ import {
   createAssociatedTokenAccount,
 } from "@solana/spl-token";
 import * as dotenv from "dotenv";
+import {
+  feeTierAccounts,
+  nextOrderPda,
+  orderStatsAccounts,
+} from "./pda";
 dotenv.config();
 
 async function main() {
@@ -28,17 +33,8 @@ async function main() {
   );
   console.log(globalConfigPda);
 
-  const timeStamp = new anchor.BN(Math.floor(Date.now() / 1000));
-
-  const [orderPda, bump] = PublicKey.findProgramAddressSync(
-    [
-      Buffer.from("dca_order"),
-      user.toBuffer(),
-      timeStamp.toArrayLike(Buffer, "le", 8),
-    ],
-    program.programId
-  );
-  console.log(timeStamp);
+  const [orderPda, bump, userState] = await nextOrderPda(program, user);
+  console.log(orderPda);
 
   const mint = new PublicKey("Gh9ZwEmdLJ8DscKNTkTqPbNwLNNBjuSzaG9Vp2KGtKJr");
   const userTokenAccount = await getOrCreateAssociatedTokenAccount(
@@ -68,7 +64,9 @@ async function main() {
     })(),
     executeCount: new anchor.BN(2),
     timeInterval: new anchor.BN(3600),
-    timeStamp: timeStamp,
+    executorTip: new anchor.BN(0),
+    referrer: null,
+    referralBps: 0,
   };
 
   await program.methods
@@ -76,10 +74,13 @@ async function main() {
     .accounts({
       order: orderPda,
       user,
+      userState,
+      ...orderStatsAccounts(program.programId, params.fromToken),
       userTokenAccount: userTokenAccount.address,
       orderTokenAccount,
       tokenMint: mint,
       global_config: globalConfigPda,
+      ...feeTierAccounts(program.programId, params.fromToken, params.toChainId),
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
       associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
@@ -101,7 +102,6 @@ async function main() {
   //   })(),
   //   executeCount: new anchor.BN(2),
   //   timeInterval: new anchor.BN(3600),
-  //   timeStamp: timeStamp,
   // };
 
   // // 执行 open_order_sol
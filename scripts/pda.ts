@@ -0,0 +1,109 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+
+// 脚本与测试共用的 PDA 推导
+
+export function userStatePda(programId: PublicKey, user: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user-state"), user.toBuffer()],
+    programId
+  )[0];
+}
+
+// 订单 PDA 由 UserState 中的 order_nonce 派生
+export async function nextOrderPda(
+  program: anchor.Program,
+  user: PublicKey
+): Promise<[PublicKey, number, PublicKey]> {
+  const userState = userStatePda(program.programId, user);
+  const state = await program.account["userState"].fetchNullable(userState);
+  const nonce: anchor.BN = state ? state.orderNonce : new anchor.BN(0);
+  const [orderPda, bump] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("dca_order"),
+      user.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  return [orderPda, bump, userState];
+}
+
+// open/execute 需要传入三个候选 fee tier PDA（可以不存在）
+export function feeTierAccounts(
+  programId: PublicKey,
+  fromToken: PublicKey,
+  toChainId: anchor.BN
+) {
+  const feeTier = (token: PublicKey, chainId: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("fee-tier"),
+        token.toBuffer(),
+        chainId.toArrayLike(Buffer, "le", 8),
+      ],
+      programId
+    )[0];
+
+  return {
+    mintChainFeeTier: feeTier(fromToken, toChainId),
+    mintFeeTier: feeTier(fromToken, new anchor.BN(0)),
+    chainFeeTier: feeTier(PublicKey.default, toChainId),
+  };
+}
+
+// open/execute/cancel 需要传入全局与 from_token 的统计 PDA
+export function orderStatsAccounts(programId: PublicKey, fromToken: PublicKey) {
+  return {
+    protocolStats: PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol-stats")],
+      programId
+    )[0],
+    mintStats: PublicKey.findProgramAddressSync(
+      [Buffer.from("mint-stats"), fromToken.toBuffer()],
+      programId
+    )[0],
+  };
+}
+
+// 目标链对应的 BridgeTarget PDA
+export function bridgeTargetPda(programId: PublicKey, toChainId: anchor.BN) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("bridge-target"), toChainId.toArrayLike(Buffer, "le", 8)],
+    programId
+  )[0];
+}
+
+// 订单路由对应的 PriceFeed PDA（可以不存在）
+export function priceFeedPda(
+  programId: PublicKey,
+  fromToken: PublicKey,
+  toChainId: anchor.BN,
+  toToken: Uint8Array
+) {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("price-feed"),
+      fromToken.toBuffer(),
+      toChainId.toArrayLike(Buffer, "le", 8),
+      Buffer.from(toToken),
+    ],
+    programId
+  )[0];
+}
+
+// 每个执行的 slice 对应一个 ExecutionReceipt PDA
+export function executionReceiptPda(
+  programId: PublicKey,
+  order: PublicKey,
+  sliceIndex: number
+) {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("execution-receipt"),
+      order.toBuffer(),
+      new anchor.BN(sliceIndex).toArrayLike(Buffer, "le", 2),
+    ],
+    programId
+  )[0];
+}
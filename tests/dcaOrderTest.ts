@@ -1,18 +1,45 @@
 import * as anchor from "@coral-xyz/anchor";
 import { AnchorError } from "@coral-xyz/anchor";
 
-import { PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  PublicKey,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
 import {
   createMint,
   getOrCreateAssociatedTokenAccount,
   mintTo,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeMintInstruction,
   getAccount,
   getAssociatedTokenAddress,
   createAssociatedTokenAccount,
+  getAssociatedTokenAddressSync,
+  NATIVE_MINT,
 } from "@solana/spl-token";
 import { expect } from "chai";
-import { createSolOrder, createSplOrder } from "./dcaOrderTestHelpers";
+import {
+  createSolOrder,
+  createSplOrder,
+  executeSolAccounts,
+  expectError,
+  u256,
+} from "./dcaOrderTestHelpers";
+import {
+  bridgeTargetPda,
+  executionReceiptPda,
+  feeTierAccounts,
+  nextOrderPda,
+  orderStatsAccounts,
+  priceFeedPda,
+  userStatePda,
+} from "../scripts/pda";
 
 describe("globalConfig test", () => {
   const provider = anchor.AnchorProvider.local();
@@ -78,7 +105,7 @@ describe("globalConfig test", () => {
     let caughtError = null;
     try {
       await program.methods
-        .updateConfig(nonOwner.publicKey, 50, nonOwner.publicKey, false)
+        .updateConfig(50, nonOwner.publicKey, false)
         .accounts({
           global_config: globalConfigPda,
           owner: nonOwner.publicKey,
@@ -111,7 +138,7 @@ describe("globalConfig test", () => {
     );
 
     await program.methods
-      .updateConfig(user, platformFee, user, false)
+      .updateConfig(platformFee, user, false)
       .accounts({
         global_config: globalConfigPda,
         owner: user,
@@ -124,10 +151,275 @@ describe("globalConfig test", () => {
     const config = await program.account["globalConfig"].fetch(globalConfigPda);
 
     expect(config.owner.toBase58()).to.equal(user.toBase58());
-    expect(config.platformFee).to.equal(platformFee);
+    // fee 变更需等待 timelock，只记录为 pending
+    expect(config.platformFee).to.equal(30);
+    expect(config.pendingPlatformFee).to.equal(platformFee);
+    expect(config.feeChangeEta.toNumber()).to.be.greaterThan(0);
     expect(config.treasury.toBase58()).to.equal(user.toBase58());
     expect(config.paused).to.equal(false);
   });
+
+  it("should reject a platform fee above the cap", async () => {
+    let caughtError = null;
+    try {
+      await program.methods
+        .updateConfig(501, user, false)
+        .accounts({
+          global_config: globalConfigPda,
+          owner: user,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("InvalidPlatformFee");
+  });
+
+  it("should refuse to migrate a config that already has the current layout", async () => {
+    await expectError(
+      program.methods
+        .migrateConfig()
+        .accounts({
+          globalConfig: globalConfigPda,
+          owner: user,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      "ConfigAlreadyMigrated"
+    );
+  });
+});
+
+describe("ownership and roles test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const newOwner = anchor.web3.Keypair.generate();
+  const pauser = anchor.web3.Keypair.generate();
+
+  it("should transfer ownership in two steps", async () => {
+    await program.methods
+      .proposeOwner(newOwner.publicKey)
+      .accounts({ globalConfig: globalConfigPda, owner: user })
+      .rpc();
+
+    let config = await program.account["globalConfig"].fetch(globalConfigPda);
+    expect(config.owner.toBase58()).to.equal(user.toBase58());
+    expect(config.pendingOwner.toBase58()).to.equal(
+      newOwner.publicKey.toBase58()
+    );
+
+    // 非 pending owner 无法接受
+    let caughtError = null;
+    try {
+      await program.methods
+        .acceptOwner()
+        .accounts({ globalConfig: globalConfigPda, pendingOwner: user })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "NotPendingOwner"
+    );
+
+    await program.methods
+      .acceptOwner()
+      .accounts({
+        globalConfig: globalConfigPda,
+        pendingOwner: newOwner.publicKey,
+      })
+      .signers([newOwner])
+      .rpc();
+
+    config = await program.account["globalConfig"].fetch(globalConfigPda);
+    expect(config.owner.toBase58()).to.equal(newOwner.publicKey.toBase58());
+    expect(config.pendingOwner.toBase58()).to.equal(
+      PublicKey.default.toBase58()
+    );
+
+    // 交还 owner，供后续测试使用
+    await program.methods
+      .proposeOwner(user)
+      .accounts({ globalConfig: globalConfigPda, owner: newOwner.publicKey })
+      .signers([newOwner])
+      .rpc();
+    await program.methods
+      .acceptOwner()
+      .accounts({ globalConfig: globalConfigPda, pendingOwner: user })
+      .rpc();
+  });
+
+  it("should let the pauser pause without being owner", async () => {
+    await program.methods
+      .setRoles(pauser.publicKey, user, user)
+      .accounts({ globalConfig: globalConfigPda, owner: user })
+      .rpc();
+
+    await program.methods
+      .setPaused(true)
+      .accounts({ globalConfig: globalConfigPda, authority: pauser.publicKey })
+      .signers([pauser])
+      .rpc();
+    let config = await program.account["globalConfig"].fetch(globalConfigPda);
+    expect(config.paused).to.equal(true);
+
+    await program.methods
+      .setPaused(false)
+      .accounts({ globalConfig: globalConfigPda, authority: pauser.publicKey })
+      .signers([pauser])
+      .rpc();
+    config = await program.account["globalConfig"].fetch(globalConfigPda);
+    expect(config.paused).to.equal(false);
+  });
+
+  it("should only let the fee manager schedule a platform fee change", async () => {
+    let caughtError = null;
+    try {
+      await program.methods
+        .setPlatformFee(100)
+        .accounts({
+          globalConfig: globalConfigPda,
+          authority: pauser.publicKey,
+        })
+        .signers([pauser])
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "Unauthorized"
+    );
+
+    const before = await program.account["globalConfig"].fetch(
+      globalConfigPda
+    );
+    const listener = await program.addEventListener(
+      "FeeChangeScheduled",
+      (event: any) => {
+        expect(event.old).to.equal(before.platformFee);
+        expect(event.new).to.equal(40);
+      }
+    );
+
+    await program.methods
+      .setPlatformFee(40)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 500));
+    await program.removeEventListener(listener);
+
+    const config = await program.account["globalConfig"].fetch(
+      globalConfigPda
+    );
+    expect(config.platformFee).to.equal(before.platformFee);
+    expect(config.pendingPlatformFee).to.equal(40);
+
+    // timelock 未到期，无法生效
+    caughtError = null;
+    try {
+      await program.methods
+        .applyPlatformFee()
+        .accounts({ globalConfig: globalConfigPda, authority: user })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "FeeChangeTimelocked"
+    );
+  });
+});
+
+describe("executorRegistry test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+  const keeper = anchor.web3.Keypair.generate();
+
+  it("should add an executor", async () => {
+    await program.methods
+      .addExecutor(keeper.publicKey)
+      .accounts({
+        executorRegistry: executorRegistryPda,
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const registry = await program.account["executorRegistry"].fetch(
+      executorRegistryPda
+    );
+    expect(registry.executors.map((k: PublicKey) => k.toBase58())).to.include(
+      keeper.publicKey.toBase58()
+    );
+  });
+
+  it("should fail if non-owner tries to add an executor", async () => {
+    const nonOwner = anchor.web3.Keypair.generate();
+    const airdropSignature = await provider.connection.requestAirdrop(
+      nonOwner.publicKey,
+      1_000_000_000 // 1 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .addExecutor(nonOwner.publicKey)
+        .accounts({
+          executorRegistry: executorRegistryPda,
+          globalConfig: globalConfigPda,
+          owner: nonOwner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([nonOwner])
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("ConstraintHasOne");
+  });
+
+  it("should remove an executor", async () => {
+    await program.methods
+      .removeExecutor(keeper.publicKey)
+      .accounts({
+        executorRegistry: executorRegistryPda,
+        globalConfig: globalConfigPda,
+        owner: user,
+      })
+      .rpc();
+
+    const registry = await program.account["executorRegistry"].fetch(
+      executorRegistryPda
+    );
+    expect(
+      registry.executors.map((k: PublicKey) => k.toBase58())
+    ).to.not.include(keeper.publicKey.toBase58());
+  });
 });
 
 describe("openOrder test", () => {
@@ -151,6 +443,12 @@ describe("openOrder test", () => {
       (event: any) => {
         try {
           expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
+          expect(event.sender.toBase58()).to.equal(user.toBase58());
+          expect(event.amountIn.toString()).to.equal(amount.toString());
+          expect(event.executeCount).to.equal(2);
+          expect(event.amountPerSlice.toString()).to.equal(
+            amount.divn(2).toString()
+          );
         } catch (e) {
           console.error("Event assertion failed:", e);
           throw e;
@@ -211,10 +509,15 @@ describe("openOrder test", () => {
     expect(orderAccount.remainingAmount.toString()).to.equal(
       openOrderParams.amountIn.toString()
     );
-    expect(orderAccount.timeStamp.toString()).to.equal(
-      openOrderParams.timeStamp.toString()
+    // time_stamp 取自链上 Clock
+    expect(orderAccount.timeStamp.toNumber()).to.equal(
+      orderAccount.nextExecutionAt.toNumber()
     );
     expect(orderAccount.bump).to.equal(bump);
+    const config = await program.account["globalConfig"].fetch(globalConfig);
+    expect(orderAccount.platformFee).to.equal(config.platformFee);
+    expect(orderAccount.lastExecutedAt.toNumber()).to.equal(0);
+    expect(orderAccount.nextExecutionAt.toNumber()).to.be.greaterThan(0);
 
     // 检查 PDA 中 lamports 是否到账
     const accountInfo = await provider.connection.getAccountInfo(orderPda);
@@ -319,10 +622,13 @@ describe("openOrder test", () => {
     expect(orderAccount.remainingAmount.toString()).to.equal(
       openOrderParams.amountIn.toString()
     );
-    expect(orderAccount.timeStamp.toString()).to.equal(
-      openOrderParams.timeStamp.toString()
+    // time_stamp 取自链上 Clock
+    expect(orderAccount.timeStamp.toNumber()).to.equal(
+      orderAccount.nextExecutionAt.toNumber()
     );
     expect(orderAccount.bump).to.equal(bump);
+    expect(orderAccount.lastExecutedAt.toNumber()).to.equal(0);
+    expect(orderAccount.nextExecutionAt.toNumber()).to.be.greaterThan(0);
 
     //9. 验证spl token是否到账
 
@@ -334,75 +640,39 @@ describe("openOrder test", () => {
       BigInt(openOrderParams.amountIn.toString())
     );
   });
-});
-
-describe("cancelOrderSol test", () => {
-  const provider = anchor.AnchorProvider.local();
-  anchor.setProvider(provider);
-
-  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
-  const user = provider.wallet.publicKey;
-
-  let globalConfigPda: PublicKey;
-  [globalConfigPda] = PublicKey.findProgramAddressSync(
-    [Buffer.from("global-config")],
-    program.programId
-  );
 
-  it("should cancel an existing SOL dca order", async () => {
-    const amountIn = new anchor.BN(1_000_000);
+  it("should seed consecutive orders with the user's nonce", async () => {
+    const userState = userStatePda(program.programId, user);
 
-    // 1. 创建一个SOL订单
-    const [orderPda, openOrderParams, bump] = await createSolOrder(
+    const [firstOrder] = await createSolOrder(
       program,
       user,
-      amountIn,
-      globalConfigPda
+      new anchor.BN(1_000_000),
+      globalConfig
     );
-
-    // 2. 添加事件监听器，监听 OrderCancelled 事件
-    const listener = await program.addEventListener(
-      "OrderCancelled",
-      (event: any, slot) => {
-        try {
-          expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
-          expect(event.canceller.toBase58()).to.equal(user.toBase58());
-        } catch (e) {
-          console.error("Event assertion failed:", e);
-          throw e;
-        }
-      }
+    const [secondOrder] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfig
     );
 
-    // 取消后余额
-    const refundReceiverBefore = await provider.connection.getBalance(user);
-
-    // 3. 调用 cancelOrderSol 方法取消订单
-    await program.methods
-      .cancelOrderSol()
-      .accounts({
-        order: orderPda,
-        user,
-        refundReceiver: user,
-        globalConfig: globalConfigPda,
-        systemProgram: SystemProgram.programId,
-      })
-      .rpc();
-
-    // 4. 等待事件处理
-    await new Promise((resolve) => setTimeout(resolve, 500));
-    await program.removeEventListener(listener);
-
-    // 5.取消后余额
-    const refundReceiverAfter = await provider.connection.getBalance(user);
-    expect(refundReceiverAfter).to.be.greaterThan(refundReceiverBefore);
+    const first = await program.account["dcaOrder"].fetch(firstOrder);
+    const second = await program.account["dcaOrder"].fetch(secondOrder);
+    expect(second.nonce.toNumber()).to.equal(first.nonce.toNumber() + 1);
 
-    const closedOrderInfo = await provider.connection.getAccountInfo(orderPda);
-    expect(closedOrderInfo).to.be.null;
+    const state = await program.account["userState"].fetch(userState);
+    expect(state.orderNonce.toNumber()).to.equal(second.nonce.toNumber() + 1);
+    const activeOrders = state.activeOrders.map((key: PublicKey) =>
+      key.toBase58()
+    );
+    expect(activeOrders).to.include.members([
+      firstOrder.toBase58(),
+      secondOrder.toBase58(),
+    ]);
   });
 
-  it("should cancel an existing SPL dca order", async () => {
-    // 1. 创建一个测试 SPL Token mint
+  it("should open into a vault created ahead of the order", async () => {
     const mint = await createMint(
       provider.connection,
       provider.wallet.payer,
@@ -410,16 +680,12 @@ describe("cancelOrderSol test", () => {
       null,
       6 // decimals
     );
-
-    // 2. 获取用户的 ATA
     const userTokenAccount = await getOrCreateAssociatedTokenAccount(
       provider.connection,
       provider.wallet.payer,
       mint,
       user
     );
-
-    // 3. 给用户 mint 一些测试 token
     await mintTo(
       provider.connection,
       provider.wallet.payer,
@@ -429,31 +695,312 @@ describe("cancelOrderSol test", () => {
       1000_000_000
     );
 
-    // 5. 创建spl订单
-    const amount = new anchor.BN(1_000_000);
-
-    const [orderPda, orderTokenAccount, openOrderParams, bump] =
-      await createSplOrder(
-        program,
-        provider,
-        user,
-        mint,
-        amount,
-        globalConfigPda
-      );
-
-    // 取消前用户token账户余额
-    const userTokenAccountBefore = await getAccount(
+    // 第三方可以根据 nonce 预先创建下一个订单的 vault 并转入 token
+    const [nextOrder] = await nextOrderPda(program, user);
+    const attacker = anchor.web3.Keypair.generate();
+    const airdropSignature = await provider.connection.requestAirdrop(
+      attacker.publicKey,
+      1_000_000_000 // 1 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+    const squatted = await createAssociatedTokenAccount(
       provider.connection,
-      userTokenAccount.address
+      attacker,
+      mint,
+      nextOrder,
+      undefined,
+      undefined,
+      undefined,
+      true
     );
-
-    // 事件监听器
-    const listener = await program.addEventListener(
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      squatted,
+      user,
+      7
+    );
+
+    const amount = new anchor.BN(1_000_000);
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(program, provider, user, mint, amount, globalConfig);
+    expect(orderPda.toBase58()).to.equal(nextOrder.toBase58());
+    expect(orderTokenAccount.toBase58()).to.equal(squatted.toBase58());
+
+    // 只记入本次转入的金额
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal(amount.toString());
+
+    // 取消时退回 vault 全部余额，vault 仍可关闭
+    await program.methods
+      .cancelOrderSpl()
+      .accounts({
+        order: orderPda,
+        user,
+        userTokenAccount: userTokenAccount.address,
+        orderTokenAccount,
+        tokenMint: mint,
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+        globalConfig,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    expect(await provider.connection.getAccountInfo(orderTokenAccount)).to.be
+      .null;
+  });
+
+  it("should escrow the net amount for a Token-2022 transfer-fee mint", async () => {
+    // 1. 创建一个带 transfer fee (1%) 的 Token-2022 mint
+    const mintKeypair = anchor.web3.Keypair.generate();
+    const mint = mintKeypair.publicKey;
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: user,
+        newAccountPubkey: mint,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mint,
+        user,
+        user,
+        100, // 1%
+        BigInt(1_000_000),
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(mint, 6, user, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await sendAndConfirmTransaction(provider.connection, tx, [
+      provider.wallet.payer,
+      mintKeypair,
+    ]);
+
+    // 2. 给用户 mint 一些测试 token
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    // 3. 创建订单，vault 实际到账金额应扣除 1% 手续费
+    const amount = new anchor.BN(1_000_000);
+    const [orderPda, orderTokenAccount, openOrderParams] = await createSplOrder(
+      program,
+      provider,
+      user,
+      mint,
+      amount,
+      globalConfig,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const vault = await getAccount(
+      provider.connection,
+      orderTokenAccount,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    expect(vault.amount).to.equal(BigInt(990_000));
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal("990000");
+
+    // 4. 取消订单：vault 里被扣留的手续费需先归集到 mint，vault 才能关闭
+    const balanceBefore = (
+      await getAccount(
+        provider.connection,
+        userTokenAccount.address,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+    ).amount;
+    await program.methods
+      .cancelOrderSpl()
+      .accounts({
+        order: orderPda,
+        user,
+        userTokenAccount: userTokenAccount.address,
+        orderTokenAccount,
+        tokenMint: mint,
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+        globalConfig,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    const balanceAfter = (
+      await getAccount(
+        provider.connection,
+        userTokenAccount.address,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+    ).amount;
+    // 退款 990_000，转入时再扣 1%
+    expect(balanceAfter - balanceBefore).to.equal(BigInt(980_100));
+    expect(await provider.connection.getAccountInfo(orderTokenAccount)).to.equal(
+      null
+    );
+  });
+});
+
+describe("cancelOrderSol test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  let globalConfigPda: PublicKey;
+  [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+
+  it("should cancel an existing SOL dca order", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+
+    // 1. 创建一个SOL订单
+    const [orderPda, openOrderParams, bump] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda
+    );
+
+    // 2. 添加事件监听器，监听 OrderCancelled 事件
+    const listener = await program.addEventListener(
+      "OrderCancelled",
+      (event: any, slot) => {
+        try {
+          expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
+          expect(event.canceller.toBase58()).to.equal(user.toBase58());
+        } catch (e) {
+          console.error("Event assertion failed:", e);
+          throw e;
+        }
+      }
+    );
+
+    // 取消后余额
+    const refundReceiverBefore = await provider.connection.getBalance(user);
+
+    // 3. 调用 cancelOrderSol 方法取消订单
+    await program.methods
+      .cancelOrderSol()
+      .accounts({
+        order: orderPda,
+        user,
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+        globalConfig: globalConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // 4. 等待事件处理
+    await new Promise((resolve) => setTimeout(resolve, 500));
+    await program.removeEventListener(listener);
+
+    // 5.取消后余额
+    const refundReceiverAfter = await provider.connection.getBalance(user);
+    expect(refundReceiverAfter).to.be.greaterThan(refundReceiverBefore);
+
+    // 订单账户保留最终状态，租金由 close_order 回收
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ cancelled: {} });
+    expect(orderAccount.remainingAmount.toNumber()).to.equal(0);
+    expect(orderAccount.cancelledAt.toNumber()).to.be.greaterThan(0);
+
+    const state = await program.account["userState"].fetch(
+      userStatePda(program.programId, user)
+    );
+    expect(
+      state.activeOrders.map((key: PublicKey) => key.toBase58())
+    ).to.not.include(orderPda.toBase58());
+    expect(state.cancelledOrders.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("should cancel an existing SPL dca order", async () => {
+    // 1. 创建一个测试 SPL Token mint
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+
+    // 2. 获取用户的 ATA
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user
+    );
+
+    // 3. 给用户 mint 一些测试 token
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000
+    );
+
+    // 5. 创建spl订单
+    const amount = new anchor.BN(1_000_000);
+
+    const [orderPda, orderTokenAccount, openOrderParams, bump] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        mint,
+        amount,
+        globalConfigPda
+      );
+
+    // 取消前用户token账户余额
+    const userTokenAccountBefore = await getAccount(
+      provider.connection,
+      userTokenAccount.address
+    );
+
+    // 事件监听器
+    const listener = await program.addEventListener(
       "OrderCancelled",
       (event: any) => {
         expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
         expect(event.by.toBase58()).to.equal(user.toBase58());
+        expect(event.refundedAmount.toString()).to.equal(amount.toString());
       }
     );
 
@@ -464,7 +1011,10 @@ describe("cancelOrderSol test", () => {
         user: user,
         userTokenAccount: userTokenAccount.address,
         orderTokenAccount: orderTokenAccount,
+        tokenMint: mint,
         refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, mint),
         globalConfig: globalConfigPda,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -484,9 +1034,9 @@ describe("cancelOrderSol test", () => {
       Number(userTokenAccountBefore.amount)
     );
 
-    // 校验 order 账户已关闭
-    const closedOrderInfo = await provider.connection.getAccountInfo(orderPda);
-    expect(closedOrderInfo).to.be.null;
+    // 校验 order 账户保留为已取消状态
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ cancelled: {} });
 
     // 校验 order_token_account 账户已关闭
     const closedTokenInfo = await provider.connection.getAccountInfo(
@@ -495,3 +1045,1851 @@ describe("cancelOrderSol test", () => {
     expect(closedTokenInfo).to.be.null;
   });
 });
+
+describe("executeOrderSol test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+
+  it("should execute one slice and reject the next one before it is due", async () => {
+    await program.methods
+      .addExecutor(user)
+      .accounts({
+        executorRegistry: executorRegistryPda,
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    // 测试里 user 同时充当 SOL 目标账户和 SPL 目标账户的 owner
+    await program.methods
+      .addBridgeTarget(new anchor.BN(10001), user)
+      .accounts({
+        bridgeTarget: bridgeTargetPda(program.programId, new anchor.BN(10001)),
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(3))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    const slice = amountIn.div(openOrderParams.executeCount);
+    expect(orderAccount.remainingAmount.toString()).to.equal(
+      amountIn.sub(slice).toString()
+    );
+    expect(orderAccount.remainingExecuteCount).to.equal(1);
+    expect(orderAccount.nextExecutionAt.toNumber()).to.equal(
+      orderAccount.lastExecutedAt.toNumber() +
+        openOrderParams.timeInterval.toNumber()
+    );
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .executeOrderSol(new anchor.BN(0), u256(3))
+        .accounts(executeSolAccounts(program, orderPda, openOrderParams, 1))
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("ExecutionTooEarly");
+  });
+
+  it("should keep a completed order until the close grace period ends", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda,
+      { executeCount: new anchor.BN(1) }
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(3))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ completed: {} });
+    expect(orderAccount.remainingAmount.toNumber()).to.equal(0);
+    expect(orderAccount.remainingExecuteCount).to.equal(0);
+    expect(orderAccount.completedAt.toNumber()).to.be.greaterThan(0);
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .closeOrder()
+        .accounts({
+          order: orderPda,
+          user,
+          refundReceiver: user,
+          globalConfig: globalConfigPda,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal(
+      "CloseGracePeriodActive"
+    );
+  });
+
+  it("should pay the executor tip out of the slice", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+    const executorTip = new anchor.BN(5_000);
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda,
+      { executorTip }
+    );
+
+    const listener = await program.addEventListener(
+      "OrderExecuted",
+      (event: any) => {
+        expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
+        expect(event.executorTip.toString()).to.equal(executorTip.toString());
+        expect(event.executeAmount.toString()).to.equal(
+          amountIn.divn(2).toString()
+        );
+        expect(event.remainingAmount.toString()).to.equal(
+          amountIn.divn(2).toString()
+        );
+        expect(event.remainingExecuteCount).to.equal(1);
+        expect(event.slot.toNumber()).to.be.greaterThan(0);
+      }
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(3))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 500));
+    await program.removeEventListener(listener);
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.executorTip.toString()).to.equal(
+      executorTip.toString()
+    );
+  });
+
+  it("should keep the referral share when the referrer can't take it", async () => {
+    const config = await program.account["globalConfig"].fetch(globalConfigPda);
+    await program.methods
+      .setMaxReferralBps(10000)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+
+    // 未入账的地址收到的分成不够免租，直接转账会让每次执行都失败
+    const referrer = anchor.web3.Keypair.generate().publicKey;
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda,
+      { referrer, referralBps: 10000 }
+    );
+
+    const listener = await program.addEventListener(
+      "OrderExecuted",
+      (event: any) => {
+        expect(event.referralAmount.toNumber()).to.equal(0);
+      }
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(3))
+      .accounts({
+        ...executeSolAccounts(program, orderPda, openOrderParams, 0),
+        referrer,
+      })
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 500));
+    await program.removeEventListener(listener);
+
+    expect(await provider.connection.getBalance(referrer)).to.equal(0);
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingExecuteCount).to.equal(1);
+
+    await program.methods
+      .setMaxReferralBps(config.maxReferralBps)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+  });
+
+  it("should reject a tip that leaves no room for the platform fee", async () => {
+    // 每片 500_000，tip 后只剩 1，不够支付平台费
+    await expectError(
+      createSolOrder(
+        program,
+        user,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        { executorTip: new anchor.BN(499_999) }
+      ),
+      "InsufficientFunds"
+    );
+  });
+
+  it("should reject a target that is not approved for the chain", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .executeOrderSol(new anchor.BN(0), u256(3))
+        .accounts({
+          ...executeSolAccounts(program, orderPda, openOrderParams, 0),
+          targetSol: anchor.web3.Keypair.generate().publicKey,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("UnapprovedTarget");
+  });
+
+  it("should reject a quote outside the slice's amount out bounds", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    // 订单 amount_out 区间为 [1, 10]，扣费后每个 slice 约为 [1, 5]
+    let caughtError = null;
+    try {
+      await program.methods
+        .executeOrderSol(new anchor.BN(0), u256(6))
+        .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("AmountOutOfBounds");
+  });
+
+  it("should round a slice's minimum up instead of down to zero", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    // 1 * send_amount / amount_in 向下取整为 0，向上取整后报价 0 不再合法
+    await expectError(
+      program.methods
+        .executeOrderSol(new anchor.BN(0), u256(0))
+        .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+        .rpc(),
+      "AmountOutOfBounds"
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(1))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+  });
+
+  it("should keep a narrow amount out range open for every slice", async () => {
+    // 区间 [1, 1]：若上限向下取整，每个 slice 的区间会变成 [1, 0]
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda,
+      { amountOutMin: u256(1), amountOutMax: u256(1) }
+    );
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(1))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+  });
+
+  it("should record a receipt per slice and confirm its delivery once", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda
+    );
+    const receiptPda = executionReceiptPda(program.programId, orderPda, 0);
+
+    await program.methods
+      .executeOrderSol(new anchor.BN(0), u256(3))
+      .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+      .rpc();
+
+    let receipt = await program.account["executionReceipt"].fetch(receiptPda);
+    expect(receipt.order.toBase58()).to.equal(orderPda.toBase58());
+    expect(receipt.sliceIndex).to.equal(0);
+    expect(receipt.executeAmount.toString()).to.equal(
+      amountIn.divn(2).toString()
+    );
+    expect(receipt.amountOut).to.deep.equal(u256(3));
+    // 上下限按扣除费用后的 sendAmount 折算，并向上取整
+    expect(receipt.amountOutMax).to.deep.equal(
+      u256(
+        receipt.sendAmount.muln(10).add(amountIn.subn(1)).div(amountIn).toNumber()
+      )
+    );
+    expect(receipt.confirmedAt.toNumber()).to.equal(0);
+
+    // 执行者不能确认自己的 slice，由另一个已注册的 executor 确认
+    const attester = anchor.web3.Keypair.generate();
+    const registryAccounts = {
+      executorRegistry: executorRegistryPda,
+      globalConfig: globalConfigPda,
+      owner: user,
+    };
+    await program.methods
+      .addExecutor(attester.publicKey)
+      .accounts({
+        ...registryAccounts,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const destTxHash = Array(32).fill(7);
+    const deliveredAmount = [5, ...Array(31).fill(0)];
+    const confirm = (signer?: anchor.web3.Keypair) =>
+      program.methods
+        .confirmDelivery(destTxHash, deliveredAmount)
+        .accounts({
+          receipt: receiptPda,
+          attester: signer ? signer.publicKey : user,
+          globalConfig: globalConfigPda,
+          executorRegistry: executorRegistryPda,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    await expectError(confirm(), "SelfAttestedDelivery");
+
+    await confirm(attester);
+    receipt = await program.account["executionReceipt"].fetch(receiptPda);
+    expect(receipt.destTxHash).to.deep.equal(destTxHash);
+    expect(receipt.deliveredAmount).to.deep.equal(deliveredAmount);
+    expect(receipt.confirmedBy.toBase58()).to.equal(
+      attester.publicKey.toBase58()
+    );
+    expect(receipt.confirmedAt.toNumber()).to.be.greaterThan(0);
+
+    await expectError(confirm(attester), "DeliveryAlreadyConfirmed");
+
+    // 确认后仍需等待宽限期才能回收租金
+    await expectError(
+      program.methods
+        .closeReceipt()
+        .accounts({ receipt: receiptPda, executor: user })
+        .rpc(),
+      "CloseGracePeriodActive"
+    );
+
+    await program.methods
+      .removeExecutor(attester.publicKey)
+      .accounts(registryAccounts)
+      .rpc();
+  });
+
+  it("should reject a referral share above the configured maximum", async () => {
+    const config = await program.account["globalConfig"].fetch(globalConfigPda);
+
+    let caughtError = null;
+    try {
+      await createSolOrder(
+        program,
+        user,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        {
+          referrer: anchor.web3.Keypair.generate().publicKey,
+          referralBps: config.maxReferralBps + 1,
+        }
+      );
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("InvalidReferral");
+  });
+});
+
+describe("executeOrderSpl test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+
+  // treasury、target 与 refund 均为 user 的 ATA
+  const executeSplSlice = (
+    orderPda: PublicKey,
+    orderTokenAccount: PublicKey,
+    openOrderParams: any,
+    userTokenAccount: PublicKey,
+    sliceIndex: number
+  ) =>
+    program.methods
+      .executeOrderSpl(new anchor.BN(0), u256(3))
+      .accounts({
+        order: orderPda,
+        orderTokenAccount,
+        targetTokenAccount: userTokenAccount,
+        bridgeTarget: bridgeTargetPda(
+          program.programId,
+          openOrderParams.toChainId
+        ),
+        treasuryTokenAccount: userTokenAccount,
+        tokenMint: openOrderParams.fromToken,
+        globalConfig: globalConfigPda,
+        executorRegistry: executorRegistryPda,
+        ...feeTierAccounts(
+          program.programId,
+          openOrderParams.fromToken,
+          openOrderParams.toChainId
+        ),
+        refundReceiver: user,
+        refundTokenAccount: userTokenAccount,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+        executor: user,
+        executorTokenAccount: null,
+        priceFeed: priceFeedPda(
+          program.programId,
+          openOrderParams.fromToken,
+          openOrderParams.toChainId,
+          openOrderParams.toToken
+        ),
+        priceUpdate: null,
+        receipt: executionReceiptPda(program.programId, orderPda, sliceIndex),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  it("should execute one slice and keep the vault open", async () => {
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000
+    );
+
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        mint,
+        amountIn,
+        globalConfigPda
+      );
+
+    const { protocolStats, mintStats } = orderStatsAccounts(
+      program.programId,
+      mint
+    );
+    const statsBefore = await program.account["protocolStats"].fetch(
+      protocolStats
+    );
+
+    // treasury 与 target 均为 user 的 ATA
+    await program.methods
+      .executeOrderSpl(new anchor.BN(0), u256(3))
+      .accounts({
+        order: orderPda,
+        orderTokenAccount,
+        targetTokenAccount: userTokenAccount.address,
+        bridgeTarget: bridgeTargetPda(
+          program.programId,
+          openOrderParams.toChainId
+        ),
+        treasuryTokenAccount: userTokenAccount.address,
+        tokenMint: mint,
+        globalConfig: globalConfigPda,
+        executorRegistry: executorRegistryPda,
+        ...feeTierAccounts(
+          program.programId,
+          openOrderParams.fromToken,
+          openOrderParams.toChainId
+        ),
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+        executor: user,
+        executorTokenAccount: null,
+        priceFeed: priceFeedPda(
+          program.programId,
+          openOrderParams.fromToken,
+          openOrderParams.toChainId,
+          openOrderParams.toToken
+        ),
+        priceUpdate: null,
+        receipt: executionReceiptPda(program.programId, orderPda, 0),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const slice = amountIn.div(openOrderParams.executeCount);
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal(
+      amountIn.sub(slice).toString()
+    );
+    expect(orderAccount.remainingExecuteCount).to.equal(1);
+
+    const vault = await getAccount(provider.connection, orderTokenAccount);
+    expect(vault.amount).to.equal(BigInt(amountIn.sub(slice).toString()));
+
+    // 新 mint 的统计从零开始
+    const mintStatsAccount = await program.account["mintStats"].fetch(
+      mintStats
+    );
+    expect(mintStatsAccount.mint.toBase58()).to.equal(mint.toBase58());
+    expect(mintStatsAccount.ordersOpened.toNumber()).to.equal(1);
+    expect(mintStatsAccount.activeOrders.toNumber()).to.equal(1);
+    expect(mintStatsAccount.volume.toString()).to.equal(slice.toString());
+
+    const statsAfter = await program.account["protocolStats"].fetch(
+      protocolStats
+    );
+    expect(statsAfter.slicesExecuted.toNumber()).to.equal(
+      statsBefore.slicesExecuted.toNumber() + 1
+    );
+    expect(statsAfter.activeOrders.toNumber()).to.equal(
+      statsBefore.activeOrders.toNumber()
+    );
+  });
+
+  it("should refund a donation to the vault when the final slice closes it", async () => {
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000
+    );
+
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        mint,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        TOKEN_PROGRAM_ID,
+        { timeInterval: new anchor.BN(1) }
+      );
+
+    const executeSlice = (sliceIndex: number) =>
+      executeSplSlice(
+        orderPda,
+        orderTokenAccount,
+        openOrderParams,
+        userTokenAccount.address,
+        sliceIndex
+      );
+
+    await executeSlice(0);
+
+    // 任何人都可以向订单的 ATA 转账，余额不为零时 vault 无法关闭
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      orderTokenAccount,
+      user,
+      7
+    );
+
+    await new Promise((r) => setTimeout(r, 2000));
+    const balanceBefore = (
+      await getAccount(provider.connection, userTokenAccount.address)
+    ).amount;
+    await executeSlice(1);
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ completed: {} });
+    expect(await provider.connection.getAccountInfo(orderTokenAccount)).to.equal(
+      null
+    );
+    // 最后一个 slice 全额转给 user，再加上退回的 7
+    const balanceAfter = (
+      await getAccount(provider.connection, userTokenAccount.address)
+    ).amount;
+    expect(balanceAfter - balanceBefore).to.equal(BigInt(500_000 + 7));
+  });
+
+  it("should complete an order whose vault was funded before it opened", async () => {
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000
+    );
+
+    // 下一个订单的 vault 已被预先创建并转入 7
+    const [nextOrder] = await nextOrderPda(program, user);
+    const prefunded = await createAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      nextOrder,
+      undefined,
+      undefined,
+      undefined,
+      true
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      prefunded,
+      user,
+      7
+    );
+
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        mint,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        TOKEN_PROGRAM_ID,
+        { executeCount: new anchor.BN(1) }
+      );
+    expect(orderTokenAccount.toBase58()).to.equal(prefunded.toBase58());
+
+    const balanceBefore = (
+      await getAccount(provider.connection, userTokenAccount.address)
+    ).amount;
+    await executeSplSlice(
+      orderPda,
+      orderTokenAccount,
+      openOrderParams,
+      userTokenAccount.address,
+      0
+    );
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ completed: {} });
+    expect(await provider.connection.getAccountInfo(orderTokenAccount)).to.equal(
+      null
+    );
+    const balanceAfter = (
+      await getAccount(provider.connection, userTokenAccount.address)
+    ).amount;
+    expect(balanceAfter - balanceBefore).to.equal(BigInt(1_000_000 + 7));
+  });
+});
+
+describe("depositWithdrawOrder test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+
+  it("should top up and partially withdraw a SOL dca order", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda
+    );
+
+    // 追加 500_000 lamports，并把剩余次数改为 3
+    await program.methods
+      .depositToOrderSol(new anchor.BN(500_000), 3)
+      .accounts({
+        order: orderPda,
+        user,
+        globalConfig: globalConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    let orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal("1500000");
+    expect(orderAccount.remainingExecuteCount).to.equal(3);
+    expect(orderAccount.amountPerSlice.toString()).to.equal("500000");
+
+    // 取回 600_000 lamports，剩余次数不变
+    await program.methods
+      .withdrawFromOrderSol(new anchor.BN(600_000), null)
+      .accounts({
+        order: orderPda,
+        user,
+        globalConfig: globalConfigPda,
+      })
+      .rpc();
+
+    orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal("900000");
+    expect(orderAccount.remainingExecuteCount).to.equal(3);
+    expect(orderAccount.amountPerSlice.toString()).to.equal("300000");
+  });
+
+  it("should fail to withdraw the whole remaining amount", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda
+    );
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .withdrawFromOrderSol(amountIn, null)
+        .accounts({
+          order: orderPda,
+          user,
+          globalConfig: globalConfigPda,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("InsufficientFunds");
+  });
+
+  it("should reject a withdrawal that leaves slices unable to pay the tip", async () => {
+    const amountIn = new anchor.BN(1_000_000);
+    const executorTip = new anchor.BN(5_000);
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda,
+      { executorTip }
+    );
+
+    // 剩余 10_000 分两次执行，每片 5_000，不够支付 tip
+    let caughtError = null;
+    try {
+      await program.methods
+        .withdrawFromOrderSol(new anchor.BN(990_000), null)
+        .accounts({
+          order: orderPda,
+          user,
+          globalConfig: globalConfigPda,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("InsufficientFunds");
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.remainingAmount.toString()).to.equal(
+      amountIn.toString()
+    );
+  });
+});
+
+describe("updateOrder test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+
+  it("should update order terms in place", async () => {
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    const newRecipient = anchor.web3.Keypair.generate().publicKey.toBytes();
+    const params = {
+      amountOutMin: new Uint8Array([2, ...Array(31).fill(0)]),
+      amountOutMax: new Uint8Array([20, ...Array(31).fill(0)]),
+      timeInterval: new anchor.BN(7200),
+      recipient: newRecipient,
+    };
+
+    const listener = await program.addEventListener(
+      "OrderUpdated",
+      (event: any) => {
+        expect(event.orderPubkey.toBase58()).to.equal(orderPda.toBase58());
+        expect(event.timeInterval.toNumber()).to.equal(7200);
+      }
+    );
+
+    await program.methods
+      .updateOrder(params)
+      .accounts({
+        order: orderPda,
+        user,
+        globalConfig: globalConfigPda,
+      })
+      .rpc();
+
+    await new Promise((r) => setTimeout(r, 500));
+    await program.removeEventListener(listener);
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(Buffer.from(orderAccount.amountOutMin)).to.eql(
+      Buffer.from(params.amountOutMin)
+    );
+    expect(Buffer.from(orderAccount.amountOutMax)).to.eql(
+      Buffer.from(params.amountOutMax)
+    );
+    expect(orderAccount.timeInterval.toNumber()).to.equal(7200);
+    expect(Buffer.from(orderAccount.recipient)).to.eql(
+      Buffer.from(newRecipient)
+    );
+  });
+
+  it("should reject a zero time_interval", async () => {
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .updateOrder({
+          amountOutMin: new Uint8Array([1, ...Array(31).fill(0)]),
+          amountOutMax: new Uint8Array([10, ...Array(31).fill(0)]),
+          timeInterval: new anchor.BN(0),
+          recipient: user.toBytes(),
+        })
+        .accounts({
+          order: orderPda,
+          user,
+          globalConfig: globalConfigPda,
+        })
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal("InvalidParameter");
+  });
+});
+
+describe("pauseResumeOrder test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+
+  it("should skip a paused order and resume on its schedule", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+    const orderAccounts = {
+      order: orderPda,
+      user,
+      globalConfig: globalConfigPda,
+    };
+
+    await program.methods.pauseOrder().accounts(orderAccounts).rpc();
+    const paused = await program.account["dcaOrder"].fetch(orderPda);
+    expect(paused.status).to.deep.equal({ paused: {} });
+
+    // 暂停期间执行者无法执行
+    let caughtError = null;
+    try {
+      await program.methods
+        .executeOrderSol(new anchor.BN(0), u256(3))
+        .accounts(executeSolAccounts(program, orderPda, openOrderParams, 0))
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "OrderPaused"
+    );
+
+    await program.methods.resumeOrder().accounts(orderAccounts).rpc();
+    const resumed = await program.account["dcaOrder"].fetch(orderPda);
+    expect(resumed.status).to.deep.equal({ active: {} });
+
+    // 下一次执行时间仍落在 time_interval 的节奏上
+    const shift = resumed.nextExecutionAt.sub(paused.nextExecutionAt);
+    expect(shift.gten(0)).to.be.true;
+    expect(shift.mod(openOrderParams.timeInterval).toNumber()).to.equal(0);
+  });
+
+  it("should only let the sender pause an order", async () => {
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+    const stranger = anchor.web3.Keypair.generate();
+
+    let caughtError = null;
+    try {
+      await program.methods
+        .pauseOrder()
+        .accounts({
+          order: orderPda,
+          user: stranger.publicKey,
+          globalConfig: globalConfigPda,
+        })
+        .signers([stranger])
+        .rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "OnlySender"
+    );
+  });
+});
+
+describe("pause and freeze test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+
+  const cancel = (orderPda: PublicKey) =>
+    program.methods
+      .cancelOrderSol()
+      .accounts({
+        order: orderPda,
+        user,
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(
+          program.programId,
+          new PublicKey("So11111111111111111111111111111111111111112")
+        ),
+        globalConfig: globalConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  it("should still allow cancellation while paused", async () => {
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    await program.methods
+      .setPaused(true)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+
+    await cancel(orderPda);
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.status).to.deep.equal({ cancelled: {} });
+
+    await program.methods
+      .setPaused(false)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+  });
+
+  it("should block cancellation while frozen", async () => {
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    await program.methods
+      .setFrozen(true)
+      .accounts({ globalConfig: globalConfigPda, owner: user })
+      .rpc();
+
+    let caughtError = null;
+    try {
+      await cancel(orderPda);
+    } catch (err) {
+      caughtError = err;
+    }
+    expect((caughtError as AnchorError).error.errorCode.code).to.equal(
+      "ProgramFrozen"
+    );
+
+    await program.methods
+      .setFrozen(false)
+      .accounts({ globalConfig: globalConfigPda, owner: user })
+      .rpc();
+    await program.methods
+      .setPaused(false)
+      .accounts({ globalConfig: globalConfigPda, authority: user })
+      .rpc();
+
+    await cancel(orderPda);
+  });
+});
+
+describe("feeTier test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const nativeToken = new PublicKey(
+    "So11111111111111111111111111111111111111112"
+  );
+  const toChainId = new anchor.BN(10001);
+  const { mintChainFeeTier } = feeTierAccounts(
+    program.programId,
+    nativeToken,
+    toChainId
+  );
+
+  it("should snapshot the matching fee tier at open", async () => {
+    await program.methods
+      .setFeeTier(nativeToken, toChainId, 10)
+      .accounts({
+        feeTier: mintChainFeeTier,
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    expect(orderAccount.platformFee).to.equal(10);
+  });
+
+  it("should fall back to the global fee once the tier is removed", async () => {
+    await program.methods
+      .removeFeeTier(nativeToken, toChainId)
+      .accounts({
+        feeTier: mintChainFeeTier,
+        globalConfig: globalConfigPda,
+        owner: user,
+      })
+      .rpc();
+
+    const [orderPda] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    const config = await program.account["globalConfig"].fetch(
+      globalConfigPda
+    );
+    expect(orderAccount.platformFee).to.equal(config.platformFee);
+  });
+});
+
+describe("priceFeed test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+  // tests/fixtures/pyth-price-update.json：price = 1000，exponent = -8
+  const priceUpdate = new PublicKey(
+    "3w1Mbo7tj5de5KhwEGVQCLt3X8kLwqqPjS1BggnCD1N4"
+  );
+  const nativeToken = new PublicKey(
+    "So11111111111111111111111111111111111111112"
+  );
+  // 独立的目标链，避免影响其他测试的订单
+  const toChainId = new anchor.BN(20001);
+  const toToken = new Uint8Array(Buffer.from("satoxi".padEnd(32, "\0")));
+  const priceFeed = priceFeedPda(
+    program.programId,
+    nativeToken,
+    toChainId,
+    toToken
+  );
+
+  const setPriceFeed = (maxStaleness: anchor.BN) =>
+    program.methods
+      .setPriceFeed({
+        fromToken: nativeToken,
+        toChainId,
+        toToken: Array.from(toToken),
+        feedId: Array(32).fill(0x11),
+        fromDecimals: 9,
+        toDecimals: 9,
+        maxStaleness,
+        maxConfidenceBps: 100,
+        maxDeviationBps: 1000,
+      })
+      .accounts({
+        priceFeed,
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  it("should gate execution on a fresh oracle price close to the quote", async () => {
+    await program.methods
+      .addBridgeTarget(toChainId, user)
+      .accounts({
+        bridgeTarget: bridgeTargetPda(program.programId, toChainId),
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await setPriceFeed(new anchor.BN(60));
+
+    const amountIn = new anchor.BN(1_000_000_000);
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda,
+      { toChainId, amountOutMax: u256(20_000) }
+    );
+    const execute = (amountOut: number, withPriceUpdate = true) =>
+      program.methods
+        .executeOrderSol(new anchor.BN(0), u256(amountOut))
+        .accounts({
+          ...executeSolAccounts(program, orderPda, openOrderParams, 0),
+          priceUpdate: withPriceUpdate ? priceUpdate : null,
+        })
+        .rpc();
+
+    await expectError(execute(5, false), "MissingPriceUpdate");
+    // mock 的 publish_time 是固定的，60 秒内必然过期
+    await expectError(execute(5), "StalePrice");
+
+    await setPriceFeed(new anchor.BN(1_000_000_000));
+    // 预言机按扣除平台费后的 sendAmount 定价：sendAmount * 1000e-8
+    const { platformFee } = await program.account["dcaOrder"].fetch(orderPda);
+    const slice = amountIn.divn(2);
+    const sendAmount = slice.sub(slice.muln(platformFee).divn(10000));
+    const expected = sendAmount.muln(1000).div(new anchor.BN(100_000_000));
+    // 报价偏低 40% 被拒绝
+    await expectError(
+      execute(expected.muln(6).divn(10).toNumber()),
+      "QuoteDeviatesFromOracle"
+    );
+    await execute(expected.toNumber());
+
+    const receipt = await program.account["executionReceipt"].fetch(
+      executionReceiptPda(program.programId, orderPda, 0)
+    );
+    expect(receipt.amountOut).to.deep.equal(u256(expected.toNumber()));
+
+    await program.methods
+      .removePriceFeed(nativeToken, toChainId, Array.from(toToken))
+      .accounts({
+        priceFeed,
+        globalConfig: globalConfigPda,
+        owner: user,
+      })
+      .rpc();
+  });
+});
+
+describe("executeOrderSwap test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+  const [swapProgramRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("swap-program-registry")],
+    program.programId
+  );
+  const whitelisted = anchor.web3.Keypair.generate().publicKey;
+  // 由 Anchor.toml 的 [[test.genesis]] 加载，见 tests/mock-swap
+  const mockSwap = new PublicKey("nbizE3isHFK5SwTT691wUpyUqfNQxjTkhTZPBf7nvV5");
+
+  const createFundedMint = async (owner: PublicKey) => {
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const tokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      owner
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      tokenAccount.address,
+      user,
+      1000_000_000
+    );
+    return [mint, tokenAccount.address];
+  };
+
+  const swapAccounts = (
+    orderPda: PublicKey,
+    orderTokenAccount: PublicKey,
+    openOrderParams: any,
+    treasuryTokenAccount: PublicKey,
+    recipientTokenAccount: PublicKey
+  ) => ({
+    order: orderPda,
+    orderTokenAccount,
+    recipientTokenAccount,
+    treasuryTokenAccount,
+    tokenMint: openOrderParams.fromToken,
+    globalConfig: globalConfigPda,
+    executorRegistry: executorRegistryPda,
+    swapProgramRegistry: swapProgramRegistryPda,
+    swapProgram: mockSwap,
+    ...feeTierAccounts(
+      program.programId,
+      openOrderParams.fromToken,
+      openOrderParams.toChainId
+    ),
+    priceFeed: priceFeedPda(
+      program.programId,
+      openOrderParams.fromToken,
+      openOrderParams.toChainId,
+      openOrderParams.toToken
+    ),
+    priceUpdate: null,
+    refundReceiver: user,
+    userState: userStatePda(program.programId, user),
+    ...orderStatsAccounts(program.programId, openOrderParams.fromToken),
+    executor: user,
+    executorTokenAccount: null,
+    referrerTokenAccount: null,
+    receipt: executionReceiptPda(program.programId, orderPda, 0),
+    tokenProgram: TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+  });
+
+  // 同链订单：to_chain_id 为 Solana，to_token 为目标 mint
+  const openSwapOrder = async (overrides = {}) => {
+    const [fromMint, userFromAccount] = await createFundedMint(user);
+    const [toMint, userToAccount] = await createFundedMint(user);
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        fromMint,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        TOKEN_PROGRAM_ID,
+        {
+          toChainId: new anchor.BN(10002),
+          toToken: toMint.toBytes(),
+          ...overrides,
+        }
+      );
+    return {
+      fromMint,
+      toMint,
+      orderPda,
+      orderTokenAccount,
+      openOrderParams,
+      // treasury 与 recipient 均为 user 的 ATA
+      accounts: swapAccounts(
+        orderPda,
+        orderTokenAccount,
+        openOrderParams,
+        userFromAccount,
+        userToAccount
+      ),
+    };
+  };
+
+  // mock swap 从 vault 取走 amountIn，并由池子向 recipient 支付 amountOut
+  const mockSwapCall = async (
+    order: Awaited<ReturnType<typeof openSwapOrder>>,
+    amountIn: anchor.BN,
+    amountOut: anchor.BN
+  ) => {
+    const pool = anchor.web3.Keypair.generate();
+    const poolOut = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      order.toMint,
+      pool.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      order.toMint,
+      poolOut.address,
+      user,
+      1000_000_000
+    );
+    const fromPool = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      order.fromMint,
+      pool.publicKey
+    );
+    const swapData = Buffer.concat([
+      amountIn.toArrayLike(Buffer, "le", 8),
+      amountOut.toArrayLike(Buffer, "le", 8),
+    ]);
+    return program.methods
+      .executeOrderSwap(swapData)
+      .accounts(order.accounts)
+      .remainingAccounts([
+        { pubkey: order.orderTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: order.fromMint, isSigner: false, isWritable: false },
+        { pubkey: fromPool.address, isSigner: false, isWritable: true },
+        { pubkey: order.orderPda, isSigner: false, isWritable: false },
+        { pubkey: poolOut.address, isSigner: false, isWritable: true },
+        { pubkey: order.toMint, isSigner: false, isWritable: false },
+        {
+          pubkey: order.accounts.recipientTokenAccount,
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: pool.publicKey, isSigner: true, isWritable: false },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ])
+      .signers([pool])
+      .rpc();
+  };
+
+  before(async () => {
+    await program.methods
+      .addSwapProgram(mockSwap)
+      .accounts({
+        swapProgramRegistry: swapProgramRegistryPda,
+        globalConfig: globalConfigPda,
+        owner: user,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("should add and remove whitelisted swap programs", async () => {
+    const accounts = {
+      swapProgramRegistry: swapProgramRegistryPda,
+      globalConfig: globalConfigPda,
+      owner: user,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods.addSwapProgram(whitelisted).accounts(accounts).rpc();
+
+    let registry = await program.account["swapProgramRegistry"].fetch(
+      swapProgramRegistryPda
+    );
+    expect(registry.programs.map((key: PublicKey) => key.toBase58())).to.include(
+      whitelisted.toBase58()
+    );
+
+    let caughtError = null;
+    try {
+      await program.methods.addSwapProgram(whitelisted).accounts(accounts).rpc();
+    } catch (err) {
+      caughtError = err;
+    }
+    const anchorError = caughtError as AnchorError;
+    expect(anchorError.error.errorCode.code).to.equal(
+      "SwapProgramAlreadyExists"
+    );
+
+    await program.methods
+      .removeSwapProgram(whitelisted)
+      .accounts({
+        swapProgramRegistry: swapProgramRegistryPda,
+        globalConfig: globalConfigPda,
+        owner: user,
+      })
+      .rpc();
+    registry = await program.account["swapProgramRegistry"].fetch(
+      swapProgramRegistryPda
+    );
+    expect(
+      registry.programs.map((key: PublicKey) => key.toBase58())
+    ).to.not.include(whitelisted.toBase58());
+  });
+
+  it("should reject a swap through a program that is not whitelisted", async () => {
+    const order = await openSwapOrder();
+
+    await expectError(
+      program.methods
+        .executeOrderSwap(Buffer.from([]))
+        .accounts({ ...order.accounts, swapProgram: TOKEN_PROGRAM_ID })
+        .rpc(),
+      "SwapProgramNotAllowed"
+    );
+  });
+
+  it("should swap the final slice, confirm its receipt and close the vault", async () => {
+    // 价格区间 1..3 to_token / from_token
+    const order = await openSwapOrder({
+      executeCount: new anchor.BN(1),
+      amountOutMin: u256(1_000_000),
+      amountOutMax: u256(3_000_000),
+    });
+    const orderAccount = await program.account["dcaOrder"].fetch(
+      order.orderPda
+    );
+    const sendAmount = new anchor.BN(1_000_000).sub(
+      new anchor.BN(1_000_000).muln(orderAccount.platformFee).divn(10_000)
+    );
+    const receivedBefore = (
+      await getAccount(
+        provider.connection,
+        order.accounts.recipientTokenAccount
+      )
+    ).amount;
+
+    await mockSwapCall(order, sendAmount, new anchor.BN(2_000_000));
+
+    const received =
+      (
+        await getAccount(
+          provider.connection,
+          order.accounts.recipientTokenAccount
+        )
+      ).amount - receivedBefore;
+    expect(received).to.equal(BigInt(2_000_000));
+
+    const receipt = await program.account["executionReceipt"].fetch(
+      order.accounts.receipt
+    );
+    const toBigInt = (value: number[]) =>
+      BigInt(new anchor.BN(value, "le").toString());
+    expect(receipt.sendAmount.toString()).to.equal(sendAmount.toString());
+    expect(toBigInt(receipt.amountOut)).to.equal(received);
+    expect(toBigInt(receipt.deliveredAmount)).to.equal(received);
+    expect(received >= toBigInt(receipt.amountOutMin)).to.equal(true);
+    expect(received <= toBigInt(receipt.amountOutMax)).to.equal(true);
+    expect(receipt.confirmedBy.toBase58()).to.equal(
+      program.programId.toBase58()
+    );
+    expect(receipt.confirmedAt.toNumber()).to.equal(
+      receipt.executedAt.toNumber()
+    );
+
+    const settled = await program.account["dcaOrder"].fetch(order.orderPda);
+    expect(settled.status).to.deep.equal({ completed: {} });
+    expect(
+      await provider.connection.getAccountInfo(order.orderTokenAccount)
+    ).to.equal(null);
+  });
+
+  it("should wrap a native SOL slice into WSOL and swap it", async () => {
+    const [toMint, userToAccount] = await createFundedMint(user);
+    // SOL 订单的平台费以 WSOL 支付给 treasury
+    const treasuryWsol = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      NATIVE_MINT,
+      user
+    );
+    const amountIn = new anchor.BN(1_000_000);
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      amountIn,
+      globalConfigPda,
+      {
+        toChainId: new anchor.BN(10002),
+        toToken: toMint.toBytes(),
+        executeCount: new anchor.BN(1),
+        amountOutMin: new Uint8Array(u256(1_000_000)),
+        amountOutMax: new Uint8Array(u256(3_000_000)),
+      }
+    );
+    // 每个 slice 临时创建的 WSOL vault
+    const orderTokenAccount = getAssociatedTokenAddressSync(
+      NATIVE_MINT,
+      orderPda,
+      true
+    );
+    const order = {
+      fromMint: NATIVE_MINT,
+      toMint,
+      orderPda,
+      orderTokenAccount,
+      openOrderParams,
+      accounts: swapAccounts(
+        orderPda,
+        orderTokenAccount,
+        openOrderParams,
+        treasuryWsol.address,
+        userToAccount
+      ),
+    };
+
+    const orderAccount = await program.account["dcaOrder"].fetch(orderPda);
+    const sendAmount = amountIn.sub(
+      amountIn.muln(orderAccount.platformFee).divn(10_000)
+    );
+    const lamportsBefore = await provider.connection.getBalance(orderPda);
+    const receivedBefore = (
+      await getAccount(provider.connection, userToAccount)
+    ).amount;
+
+    await mockSwapCall(order, sendAmount, new anchor.BN(2_000_000));
+
+    expect(
+      (await getAccount(provider.connection, userToAccount)).amount -
+        receivedBefore
+    ).to.equal(BigInt(2_000_000));
+    expect(await provider.connection.getBalance(orderPda)).to.equal(
+      lamportsBefore - amountIn.toNumber()
+    );
+    const settled = await program.account["dcaOrder"].fetch(orderPda);
+    expect(settled.status).to.deep.equal({ completed: {} });
+    // WSOL vault 在 slice 结束时关闭，租金退回 executor
+    expect(await provider.connection.getAccountInfo(orderTokenAccount)).to.be
+      .null;
+  });
+
+  it("should reject a swap that spends more than the slice", async () => {
+    const order = await openSwapOrder();
+    const orderAccount = await program.account["dcaOrder"].fetch(
+      order.orderPda
+    );
+    const slice = new anchor.BN(1_000_000).divn(2);
+    const sendAmount = slice.sub(
+      slice.muln(orderAccount.platformFee).divn(10_000)
+    );
+
+    await expectError(
+      mockSwapCall(order, sendAmount.addn(1), new anchor.BN(1_000)),
+      "InvalidSwapResult"
+    );
+  });
+});
+
+// 放在最后：三条执行路径的 bridge target、swap 程序白名单都已由前面的测试配置好
+describe("unauthorized executor test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+  const user = provider.wallet.publicKey;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const [executorRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-registry")],
+    program.programId
+  );
+  const [swapProgramRegistryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("swap-program-registry")],
+    program.programId
+  );
+  const mockSwap = new PublicKey("nbizE3isHFK5SwTT691wUpyUqfNQxjTkhTZPBf7nvV5");
+  const stranger = anchor.web3.Keypair.generate();
+
+  // 共用 SPL 订单的账户，executor 为未注册的 stranger
+  const splAccounts = async (toChainId: anchor.BN, toToken?: PublicKey) => {
+    const mint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      user
+    );
+    await mintTo(
+      provider.connection,
+      provider.wallet.payer,
+      mint,
+      userTokenAccount.address,
+      user,
+      1000_000_000
+    );
+    const [orderPda, orderTokenAccount, openOrderParams] =
+      await createSplOrder(
+        program,
+        provider,
+        user,
+        mint,
+        new anchor.BN(1_000_000),
+        globalConfigPda,
+        TOKEN_PROGRAM_ID,
+        toToken ? { toChainId, toToken: toToken.toBytes() } : { toChainId }
+      );
+    return {
+      userTokenAccount: userTokenAccount.address,
+      accounts: {
+        order: orderPda,
+        orderTokenAccount,
+        treasuryTokenAccount: userTokenAccount.address,
+        tokenMint: mint,
+        globalConfig: globalConfigPda,
+        executorRegistry: executorRegistryPda,
+        ...feeTierAccounts(program.programId, mint, toChainId),
+        priceFeed: priceFeedPda(
+          program.programId,
+          mint,
+          toChainId,
+          openOrderParams.toToken
+        ),
+        priceUpdate: null,
+        refundReceiver: user,
+        userState: userStatePda(program.programId, user),
+        ...orderStatsAccounts(program.programId, mint),
+        executor: stranger.publicKey,
+        executorTokenAccount: null,
+        receipt: executionReceiptPda(program.programId, orderPda, 0),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    };
+  };
+
+  before(async () => {
+    // receipt 由 executor 付租金
+    const airdropSignature = await provider.connection.requestAirdrop(
+      stranger.publicKey,
+      1_000_000_000 // 1 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+  });
+
+  it("should reject a SOL slice from an executor outside the registry", async () => {
+    const [orderPda, openOrderParams] = await createSolOrder(
+      program,
+      user,
+      new anchor.BN(1_000_000),
+      globalConfigPda
+    );
+
+    await expectError(
+      program.methods
+        .executeOrderSol(new anchor.BN(0), u256(3))
+        .accounts({
+          ...executeSolAccounts(program, orderPda, openOrderParams, 0),
+          executor: stranger.publicKey,
+        })
+        .signers([stranger])
+        .rpc(),
+      "UnauthorizedExecutor"
+    );
+  });
+
+  it("should reject an SPL slice from an executor outside the registry", async () => {
+    const toChainId = new anchor.BN(10001);
+    const { userTokenAccount, accounts } = await splAccounts(toChainId);
+
+    await expectError(
+      program.methods
+        .executeOrderSpl(new anchor.BN(0), u256(3))
+        .accounts({
+          ...accounts,
+          targetTokenAccount: userTokenAccount,
+          bridgeTarget: bridgeTargetPda(program.programId, toChainId),
+        })
+        .signers([stranger])
+        .rpc(),
+      "UnauthorizedExecutor"
+    );
+  });
+
+  it("should reject a swap slice from an executor outside the registry", async () => {
+    const toMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      user,
+      null,
+      6 // decimals
+    );
+    const recipientTokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      toMint,
+      user
+    );
+    const { accounts } = await splAccounts(new anchor.BN(10002), toMint);
+
+    await expectError(
+      program.methods
+        .executeOrderSwap(Buffer.from([]))
+        .accounts({
+          ...accounts,
+          recipientTokenAccount: recipientTokenAccount.address,
+          swapProgramRegistry: swapProgramRegistryPda,
+          swapProgram: mockSwap,
+          referrerTokenAccount: null,
+          associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        })
+        .signers([stranger])
+        .rpc(),
+      "UnauthorizedExecutor"
+    );
+  });
+});
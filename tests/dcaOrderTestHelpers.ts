@@ -1,9 +1,21 @@
 import * as anchor from "@coral-xyz/anchor";
+import { AnchorError } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram } from "@solana/web3.js";
 import {
+  getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  bridgeTargetPda,
+  executionReceiptPda,
+  feeTierAccounts,
+  nextOrderPda,
+  orderStatsAccounts,
+  priceFeedPda,
+  userStatePda,
+} from "../scripts/pda";
 
 type OpenOrderParams = {
   fromToken: PublicKey;
@@ -16,24 +28,24 @@ type OpenOrderParams = {
   recipient: Uint8Array; // [u8; 32]
   executeCount: anchor.BN; // u16
   timeInterval: anchor.BN;
-  timeStamp: anchor.BN;
+  executorTip: anchor.BN;
+  referrer: PublicKey | null;
+  referralBps: number; // u16
 };
 
+// [u8; 32] 小端表示的 u256
+export function u256(value: number): number[] {
+  return Array.from(new anchor.BN(value).toArrayLike(Buffer, "le", 32));
+}
+
 export async function createSolOrder(
   program: anchor.Program,
   user: PublicKey,
   amountIn: anchor.BN,
-  globalConfig: PublicKey
+  globalConfig: PublicKey,
+  overrides: Partial<OpenOrderParams> = {}
 ): Promise<[PublicKey, OpenOrderParams, number]> {
-  const timeStamp = new anchor.BN(Math.floor(Date.now() / 1000));
-  const [orderPda, bump] = PublicKey.findProgramAddressSync(
-    [
-      Buffer.from("dca_order"),
-      user.toBuffer(),
-      timeStamp.toArrayLike(Buffer, "le", 8),
-    ],
-    program.programId
-  );
+  const [orderPda, bump, userState] = await nextOrderPda(program, user);
 
   const params = {
     fromToken: new PublicKey("So11111111111111111111111111111111111111112"), // native SOL
@@ -50,7 +62,10 @@ export async function createSolOrder(
     })(),
     executeCount: new anchor.BN(2),
     timeInterval: new anchor.BN(3600),
-    timeStamp: timeStamp,
+    executorTip: new anchor.BN(0),
+    referrer: null,
+    referralBps: 0,
+    ...overrides,
   };
 
   // 执行 open_order_sol
@@ -59,7 +74,10 @@ export async function createSolOrder(
     .accounts({
       order: orderPda,
       user: user,
+      userState,
+      ...orderStatsAccounts(program.programId, params.fromToken),
       global_config: globalConfig,
+      ...feeTierAccounts(program.programId, params.fromToken, params.toChainId),
       systemProgram: SystemProgram.programId,
     })
     .rpc();
@@ -67,35 +85,72 @@ export async function createSolOrder(
   return [orderPda, params, bump];
 }
 
+// execute_order_sol 的账户：provider 钱包同时是 sender、executor、treasury 和目标账户
+export function executeSolAccounts(
+  program: anchor.Program,
+  orderPda: PublicKey,
+  params: OpenOrderParams,
+  sliceIndex: number
+) {
+  const user = program.provider.publicKey;
+  return {
+    order: orderPda,
+    targetSol: user,
+    bridgeTarget: bridgeTargetPda(program.programId, params.toChainId),
+    treasury: user,
+    executor: user,
+    globalConfig: PublicKey.findProgramAddressSync(
+      [Buffer.from("global-config")],
+      program.programId
+    )[0],
+    executorRegistry: PublicKey.findProgramAddressSync(
+      [Buffer.from("executor-registry")],
+      program.programId
+    )[0],
+    ...feeTierAccounts(program.programId, params.fromToken, params.toChainId),
+    userState: userStatePda(program.programId, user),
+    ...orderStatsAccounts(program.programId, params.fromToken),
+    priceFeed: priceFeedPda(
+      program.programId,
+      params.fromToken,
+      params.toChainId,
+      params.toToken
+    ),
+    priceUpdate: null,
+    receipt: executionReceiptPda(program.programId, orderPda, sliceIndex),
+    systemProgram: SystemProgram.programId,
+  };
+}
+
 export async function createSplOrder(
   program: anchor.Program,
   provider: anchor.AnchorProvider,
   user: PublicKey,
   mint: PublicKey,
   amountIn: anchor.BN,
-  globalConfig: PublicKey
+  globalConfig: PublicKey,
+  tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+  overrides: Partial<OpenOrderParams> = {}
 ): Promise<[PublicKey, PublicKey, OpenOrderParams, number]> {
-  const timeStamp = new anchor.BN(Math.floor(Date.now() / 1000));
-  const [orderPda, bump] = PublicKey.findProgramAddressSync(
-    [
-      Buffer.from("dca_order"),
-      user.toBuffer(),
-      timeStamp.toArrayLike(Buffer, "le", 8),
-    ],
-    program.programId
-  );
+  const [orderPda, bump, userState] = await nextOrderPda(program, user);
 
   const userTokenAccount = await getOrCreateAssociatedTokenAccount(
     provider.connection,
     provider.wallet.payer,
     mint,
-    user
+    user,
+    false,
+    undefined,
+    undefined,
+    tokenProgram
   );
 
-  const orderTokenAccount = anchor.utils.token.associatedAddress({
+  const orderTokenAccount = getAssociatedTokenAddressSync(
     mint,
-    owner: orderPda,
-  });
+    orderPda,
+    true,
+    tokenProgram
+  );
 
   const params = {
     fromToken: mint, // spl token
@@ -112,7 +167,10 @@ export async function createSplOrder(
     })(),
     executeCount: new anchor.BN(2),
     timeInterval: new anchor.BN(3600),
-    timeStamp: timeStamp,
+    executorTip: new anchor.BN(0),
+    referrer: null,
+    referralBps: 0,
+    ...overrides,
   };
 
   await program.methods
@@ -120,10 +178,13 @@ export async function createSplOrder(
     .accounts({
       order: orderPda,
       user,
+      userState,
+      ...orderStatsAccounts(program.programId, params.fromToken),
       userTokenAccount: userTokenAccount.address,
       orderTokenAccount,
+      ...feeTierAccounts(program.programId, params.fromToken, params.toChainId),
       tokenMint: mint,
-      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenProgram,
       systemProgram: SystemProgram.programId,
       associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
     })
@@ -131,3 +192,15 @@ export async function createSplOrder(
 
   return [orderPda, orderTokenAccount, params, bump];
 }
+
+// 断言调用以指定的 AnchorError 失败
+export async function expectError(call: Promise<unknown>, code: string) {
+  let caughtError = null;
+  try {
+    await call;
+  } catch (err) {
+    caughtError = err;
+  }
+  const anchorError = caughtError as AnchorError;
+  expect(anchorError.error.errorCode.code).to.equal(code);
+}
@@ -0,0 +1,54 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import * as fs from "fs";
+
+describe("migrateConfig high fee test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+
+  const owner = Keypair.fromSecretKey(
+    Uint8Array.from(
+      JSON.parse(
+        fs.readFileSync("tests/fixtures/legacy-config-owner.json", "utf8")
+      )
+    )
+  );
+  // 与 lib.rs 中的 MAX_PLATFORM_FEE 一致
+  const maxPlatformFee = 500;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+
+  it("should clamp a baseline fee above the cap", async () => {
+    const airdropSignature = await provider.connection.requestAirdrop(
+      owner.publicKey,
+      1_000_000_000 // 1 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+
+    // fixture 中的 platform_fee 为 9999
+    const before = await provider.connection.getAccountInfo(globalConfigPda);
+    expect(before.data.readUInt16LE(40)).to.equal(9999);
+
+    await program.methods
+      .migrateConfig()
+      .accounts({
+        globalConfig: globalConfigPda,
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const config = await program.account["globalConfig"].fetch(
+      globalConfigPda
+    );
+    expect(config.platformFee).to.equal(maxPlatformFee);
+    expect(config.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+  });
+});
@@ -0,0 +1,78 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import * as fs from "fs";
+import { expectError } from "../dcaOrderTestHelpers";
+
+describe("cancelLegacyOrder test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+
+  // 与 tests/fixtures/legacy-sol-order.json 中的字段一致
+  const owner = Keypair.fromSecretKey(
+    Uint8Array.from(
+      JSON.parse(
+        fs.readFileSync("tests/fixtures/legacy-config-owner.json", "utf8")
+      )
+    )
+  );
+  const sender = new PublicKey("97fD2HfhGbNnyzWuPqWLp4SzWr8mkDTy1omJZk2UiR84");
+  const timeStamp = new anchor.BN(1_700_000_000);
+  const remainingAmount = 1_000_000;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  // 旧版 seeds 使用客户端传入的 time_stamp
+  const [orderPda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("dca_order"),
+      sender.toBuffer(),
+      timeStamp.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+
+  it("should reject a caller who is neither the sender nor the owner", async () => {
+    await expectError(
+      program.methods
+        .cancelLegacyOrderSol()
+        .accounts({
+          order: orderPda,
+          user: provider.wallet.publicKey,
+          refundReceiver: sender,
+          globalConfig: globalConfigPda,
+        })
+        .rpc(),
+      "OnlySenderOrOwner"
+    );
+  });
+
+  it("should refund and close a baseline SOL order", async () => {
+    const order = await provider.connection.getAccountInfo(orderPda);
+    expect(order.data.length).to.equal(261);
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(
+      order.data.length
+    );
+    expect(order.lamports).to.equal(rent + remainingAmount);
+
+    await program.methods
+      .cancelLegacyOrderSol()
+      .accounts({
+        order: orderPda,
+        user: owner.publicKey,
+        refundReceiver: sender,
+        globalConfig: globalConfigPda,
+      })
+      .signers([owner])
+      .rpc();
+
+    expect(await provider.connection.getAccountInfo(orderPda)).to.equal(null);
+    expect(await provider.connection.getBalance(sender)).to.equal(
+      rent + remainingAmount
+    );
+  });
+});
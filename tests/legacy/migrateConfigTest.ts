@@ -0,0 +1,87 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import * as fs from "fs";
+import { expectError } from "../dcaOrderTestHelpers";
+
+describe("migrateConfig test", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.interraDcaOrderSolana as anchor.Program;
+
+  // 与 tests/fixtures/legacy-global-config.json 中的字段一致
+  const owner = Keypair.fromSecretKey(
+    Uint8Array.from(
+      JSON.parse(
+        fs.readFileSync("tests/fixtures/legacy-config-owner.json", "utf8")
+      )
+    )
+  );
+  const treasury = new PublicKey(
+    "JDpAWVvnURXnGfM4zctiVuCCwbthjzyEs6WpGV1TbpsT"
+  );
+  const platformFee = 30;
+
+  const [globalConfigPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("global-config")],
+    program.programId
+  );
+  const accounts = {
+    globalConfig: globalConfigPda,
+    owner: owner.publicKey,
+    systemProgram: SystemProgram.programId,
+  };
+
+  before(async () => {
+    // owner 需要补足扩容后的租金
+    const airdropSignature = await provider.connection.requestAirdrop(
+      owner.publicKey,
+      1_000_000_000 // 1 SOL
+    );
+    await provider.connection.confirmTransaction(airdropSignature);
+  });
+
+  it("should grow a baseline config to the current layout", async () => {
+    const before = await provider.connection.getAccountInfo(globalConfigPda);
+    expect(before.data.length).to.equal(203);
+
+    await program.methods
+      .migrateConfig()
+      .accounts(accounts)
+      .signers([owner])
+      .rpc();
+
+    const after = await provider.connection.getAccountInfo(globalConfigPda);
+    const newLen = after.data.length;
+    expect(newLen).to.be.greaterThan(203);
+    expect(after.lamports).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(newLen)
+    );
+
+    const config = await program.account["globalConfig"].fetch(
+      globalConfigPda
+    );
+    expect(config.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(config.treasury.toBase58()).to.equal(treasury.toBase58());
+    expect(config.platformFee).to.equal(platformFee);
+    expect(config.paused).to.equal(false);
+    // 新增字段从零开始
+    expect(config.pendingOwner.toBase58()).to.equal(
+      PublicKey.default.toBase58()
+    );
+    expect(config.frozen).to.equal(false);
+    expect(config.maxReferralBps).to.equal(0);
+  });
+
+  it("should reject migrating a config that is already current", async () => {
+    await expectError(
+      program.methods
+        .migrateConfig()
+        .accounts(accounts)
+        .signers([owner])
+        .rpc(),
+      "ConfigAlreadyMigrated"
+    );
+  });
+});
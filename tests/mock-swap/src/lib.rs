@@ -0,0 +1,88 @@
+//! Stand-in swap program for the `execute_order_swap` tests, loaded into the
+//! test validator through `[[test.genesis]]`. It moves exactly the amounts it
+//! is given, so a test can make a swap deliver, overspend or underspend.
+//!
+//! Instruction data is `amount_in: u64` then `amount_out: u64`, little-endian.
+//! Accounts:
+//!   0. `[writable]` source, the order vault
+//!   1. `[]` source mint
+//!   2. `[writable]` pool account receiving `amount_in`
+//!   3. `[signer]` source authority, the order PDA
+//!   4. `[writable]` pool account paying `amount_out`
+//!   5. `[]` destination mint
+//!   6. `[writable]` destination, the order recipient's account
+//!   7. `[signer]` pool authority
+//!   8. `[]` token program
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_spl::token_interface::{transfer_checked, Mint, TransferChecked};
+
+#[cfg(not(feature = "no-entrypoint"))]
+anchor_lang::solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    swap(accounts, data).map_err(Into::into)
+}
+
+fn swap(accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+    let [source, source_mint, pool_in, source_authority, pool_out, destination_mint, destination, pool_authority, token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    };
+    let amount_in = read_u64(data, 0)?;
+    let amount_out = read_u64(data, 8)?;
+
+    pay(
+        token_program,
+        source,
+        source_mint,
+        pool_in,
+        source_authority,
+        amount_in,
+    )?;
+    pay(
+        token_program,
+        pool_out,
+        destination_mint,
+        destination,
+        pool_authority,
+        amount_out,
+    )
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or_else(|| ProgramError::InvalidInstructionData.into())
+}
+
+fn pay<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let decimals = Mint::try_deserialize(&mut &mint.data.borrow()[..])?.decimals;
+    transfer_checked(
+        CpiContext::new(
+            token_program.clone(),
+            TransferChecked {
+                from: from.clone(),
+                mint: mint.clone(),
+                to: to.clone(),
+                authority: authority.clone(),
+            },
+        ),
+        amount,
+        decimals,
+    )
+}